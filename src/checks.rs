@@ -0,0 +1,418 @@
+//! Consistency checks of a statement against its own figures.
+
+use crate::model::{Account, BalanceType, CreditDebit, CreditorReference, EntryStatus, Statement};
+use crate::money::{Currency, Money};
+use crate::reference;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Opening balance plus booked entries compared with the closing balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconciliation {
+    pub currency: Currency,
+    /// signed opening balance, `OPBD` or else `PRCD`
+    pub opening: Decimal,
+    /// sum of booked credit entries
+    pub credits: Decimal,
+    /// sum of booked debit entries
+    pub debits: Decimal,
+    /// signed closing balance, `CLBD` or else `ITBD` for intraday reports
+    pub closing: Decimal,
+    /// booked entries in another currency than the balances, left out,
+    /// with their 1-based index in the statement
+    pub other_currencies: Vec<(usize, Money)>,
+}
+
+impl Reconciliation {
+    /// What the closing balance should be according to the entries.
+    pub fn expected_closing(&self) -> Decimal {
+        self.opening + self.credits - self.debits
+    }
+
+    /// Closing balance minus expected closing, zero when all is well.
+    pub fn difference(&self) -> Decimal {
+        self.closing - self.expected_closing()
+    }
+
+    pub fn is_balanced(&self) -> bool {
+        self.difference().is_zero() && self.other_currencies.is_empty()
+    }
+}
+
+impl fmt::Display for Reconciliation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "opening {} + credits {} - debits {} = {}, closing {} {}",
+            self.opening,
+            self.credits,
+            self.debits,
+            self.expected_closing(),
+            self.closing,
+            self.currency
+        )?;
+        if !self.difference().is_zero() {
+            write!(f, ", difference {}", self.difference())?;
+        }
+        for (entry, amount) in &self.other_currencies {
+            write!(f, ", entry {} of {} left out", entry, amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the balances of a statement against its entries. Pending and
+/// information entries do not move the booked balance and are left out.
+/// `None` if the statement lacks an opening or closing balance.
+pub fn reconcile(statement: &Statement) -> Option<Reconciliation> {
+    let opening = statement
+        .balance(&BalanceType::Opening)
+        .or_else(|| statement.balance(&BalanceType::PreviouslyClosed))?;
+    let closing = statement
+        .balance(&BalanceType::Closing)
+        .or_else(|| statement.balance(&BalanceType::InterimBooked))?;
+
+    let currency = opening.amount.currency.clone();
+    let mut credits = Money::zero(currency.clone());
+    let mut debits = Money::zero(currency.clone());
+    let mut other_currencies = Vec::new();
+    let booked = statement
+        .entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| matches!(entry.status, None | Some(EntryStatus::Booked)));
+    for (index, entry) in booked {
+        let total = match entry.credit_debit {
+            CreditDebit::Credit => &mut credits,
+            CreditDebit::Debit => &mut debits,
+        };
+        match total.checked_add(&entry.amount) {
+            Some(sum) => *total = sum,
+            None => other_currencies.push((index + 1, entry.amount.clone())),
+        }
+    }
+
+    Some(Reconciliation {
+        currency,
+        opening: opening.signed_amount(),
+        credits: credits.amount,
+        debits: debits.amount,
+        closing: closing.signed_amount(),
+        other_currencies,
+    })
+}
+
+/// A figure of `TxsSummry` that the parsed entries do not add up to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummaryMismatch {
+    /// e.g. `TtlCdtNtries/Sum`
+    pub field: &'static str,
+    /// what the bank wrote
+    pub expected: String,
+    /// what the entries give
+    pub found: String,
+}
+
+impl fmt::Display for SummaryMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: summary says {}, entries give {}",
+            self.field, self.expected, self.found
+        )
+    }
+}
+
+/// Compare `TxsSummry` with the entries, all of them whatever their status.
+/// Entries skipped in lenient mode show up here as missing.
+pub fn check_summary(statement: &Statement) -> Vec<SummaryMismatch> {
+    let mut mismatches = Vec::new();
+    let summary = match &statement.summary {
+        Some(summary) => summary,
+        None => return mismatches,
+    };
+
+    let mut compare = |field: &'static str, expected: Option<String>, found: String| {
+        if let Some(expected) = expected {
+            if expected != found {
+                mismatches.push(SummaryMismatch {
+                    field,
+                    expected,
+                    found,
+                });
+            }
+        }
+    };
+
+    let count_and_sum = |credit_debit: Option<CreditDebit>| {
+        let entries = statement
+            .entries
+            .iter()
+            .filter(|entry| credit_debit.is_none() || credit_debit == Some(entry.credit_debit));
+        entries.fold((0u64, Decimal::ZERO), |(count, sum), entry| {
+            (count + 1, sum + entry.amount.amount)
+        })
+    };
+
+    let totals = [
+        (
+            "TtlNtries/NbOfNtries",
+            "TtlNtries/Sum",
+            &summary.total,
+            None,
+        ),
+        (
+            "TtlCdtNtries/NbOfNtries",
+            "TtlCdtNtries/Sum",
+            &summary.total_credit,
+            Some(CreditDebit::Credit),
+        ),
+        (
+            "TtlDbtNtries/NbOfNtries",
+            "TtlDbtNtries/Sum",
+            &summary.total_debit,
+            Some(CreditDebit::Debit),
+        ),
+    ];
+    for (count_field, sum_field, total, credit_debit) in totals {
+        if let Some(total) = total {
+            let (count, sum) = count_and_sum(credit_debit);
+            compare(
+                count_field,
+                total.number_of_entries.map(|number| number.to_string()),
+                count.to_string(),
+            );
+            // compare values, not their scale: 12.5 is 12.50
+            compare(
+                sum_field,
+                total.sum.map(|sum| sum.normalize().to_string()),
+                sum.normalize().to_string(),
+            );
+        }
+    }
+
+    let (_, credits) = count_and_sum(Some(CreditDebit::Credit));
+    let (_, debits) = count_and_sum(Some(CreditDebit::Debit));
+    compare(
+        "TtlNtries/TtlNetNtry",
+        summary.total_net.map(|net| net.normalize().to_string()),
+        (credits - debits).normalize().to_string(),
+    );
+
+    mismatches
+}
+
+/// A creditor reference whose check digits are wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidReference {
+    /// 1-based index of the entry in the statement
+    pub entry: usize,
+    pub reference: CreditorReference,
+}
+
+impl fmt::Display for InvalidReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entry {}: invalid {} reference {}",
+            self.entry,
+            self.reference
+                .reference_type
+                .as_ref()
+                .map(|reference_type| reference_type.code())
+                .unwrap_or_default(),
+            self.reference.reference
+        )
+    }
+}
+
+/// Check the QR, ISR and RF creditor references of all transactions.
+pub fn check_references(statement: &Statement) -> Vec<InvalidReference> {
+    let mut invalid = Vec::new();
+    for (index, entry) in statement.entries.iter().enumerate() {
+        let references = entry
+            .details
+            .iter()
+            .flat_map(|details| details.transactions.iter())
+            .filter_map(|tx| tx.remittance_information.as_ref())
+            .flat_map(|rmt_inf| rmt_inf.structured.iter())
+            .filter_map(|strd| strd.creditor_reference.as_ref());
+        for reference in references {
+            if reference.is_valid() == Some(false) {
+                invalid.push(InvalidReference {
+                    entry: index + 1,
+                    reference: reference.clone(),
+                });
+            }
+        }
+    }
+    invalid
+}
+
+/// An IBAN or BIC that is not well formed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidIdentifier {
+    /// 1-based index of the entry, `None` for the statement account
+    pub entry: Option<usize>,
+    /// where it was found, e.g. `DbtrAcct` or `CdtrAgt`
+    pub field: &'static str,
+    pub value: String,
+    /// e.g. `wrong check digits`
+    pub problem: &'static str,
+}
+
+impl fmt::Display for InvalidIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(entry) = self.entry {
+            write!(f, "entry {}: ", entry)?;
+        }
+        write!(f, "{} {}: {}", self.field, self.value, self.problem)
+    }
+}
+
+/// The IBAN of `account` if it is invalid, accounts without one are not
+/// checked.
+fn invalid_iban(
+    entry: Option<usize>,
+    field: &'static str,
+    account: &Account,
+) -> Option<InvalidIdentifier> {
+    account.iban.as_ref()?;
+    let iban = account.identifier()?;
+    let problem = reference::iban_problem(&iban)?;
+    Some(InvalidIdentifier {
+        entry,
+        field,
+        value: iban,
+        problem,
+    })
+}
+
+/// Check the IBANs of the statement account and of the counterparty
+/// accounts, and the BICs of the agents.
+pub fn check_identifiers(statement: &Statement) -> Vec<InvalidIdentifier> {
+    let mut invalid = Vec::new();
+    invalid.extend(invalid_iban(None, "Acct", &statement.account));
+
+    for (index, entry) in statement.entries.iter().enumerate() {
+        let transactions = entry
+            .details
+            .iter()
+            .flat_map(|details| details.transactions.iter());
+        for tx in transactions {
+            if let Some(parties) = &tx.related_parties {
+                let accounts = [
+                    ("DbtrAcct", &parties.debtor_account),
+                    ("CdtrAcct", &parties.creditor_account),
+                ];
+                for (field, account) in accounts {
+                    if let Some(account) = account {
+                        invalid.extend(invalid_iban(Some(index + 1), field, account));
+                    }
+                }
+            }
+            if let Some(agents) = &tx.related_agents {
+                let agents = [
+                    ("DbtrAgt", &agents.debtor_agent),
+                    ("CdtrAgt", &agents.creditor_agent),
+                ];
+                for (field, agent) in agents {
+                    let bic = agent.as_ref().and_then(|agent| agent.bic.as_ref());
+                    if let Some(bic) = bic.filter(|bic| !reference::is_valid_bic(bic)) {
+                        invalid.push(InvalidIdentifier {
+                            entry: Some(index + 1),
+                            field,
+                            value: bic.clone(),
+                            problem: "not a BIC",
+                        });
+                    }
+                }
+            }
+        }
+    }
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{balance, camt053, entry, statement};
+
+    fn statement_with(closing: &str) -> Statement {
+        let body = [
+            balance("OPBD", "1000.00"),
+            balance("CLBD", closing),
+            entry("250.00", "CRDT", "BOOK", "A1", ""),
+            entry("69.50", "DBIT", "BOOK", "A2", ""),
+            entry("500.00", "CRDT", "PDNG", "A3", ""),
+        ]
+        .concat();
+        camt053(&[statement("S1", 1, 1, 31, &body)]).remove(0)
+    }
+
+    #[test]
+    fn balanced_statement() {
+        let check = reconcile(&statement_with("1180.50")).expect("has balances");
+        assert!(check.is_balanced());
+        assert_eq!(check.expected_closing(), Decimal::new(118050, 2));
+    }
+
+    #[test]
+    fn unbalanced_statement() {
+        let check = reconcile(&statement_with("1200.00")).expect("has balances");
+        assert!(!check.is_balanced());
+        assert_eq!(check.difference(), Decimal::new(1950, 2));
+    }
+
+    #[test]
+    fn entry_in_other_currency() {
+        let body = [
+            balance("OPBD", "1000.00"),
+            balance("CLBD", "1000.00"),
+            entry("69.50", "DBIT", "BOOK", "A1", "").replace("CHF", "USD"),
+        ]
+        .concat();
+        let statement = camt053(&[statement("S1", 1, 1, 31, &body)]).remove(0);
+        let check = reconcile(&statement).expect("has balances");
+        assert!(!check.is_balanced());
+        assert_eq!(check.other_currencies.len(), 1);
+        assert_eq!(check.other_currencies[0].0, 1);
+    }
+
+    fn summary(credits: &str) -> String {
+        format!(
+            "<TxsSummry><TtlNtries><NbOfNtries>2</NbOfNtries><Sum>319.50</Sum>\
+             <TtlNetNtry><Amt>180.50</Amt><CdtDbtInd>CRDT</CdtDbtInd></TtlNetNtry></TtlNtries>\
+             <TtlCdtNtries><NbOfNtries>1</NbOfNtries><Sum>{}</Sum></TtlCdtNtries>\
+             <TtlDbtNtries><NbOfNtries>1</NbOfNtries><Sum>69.5</Sum></TtlDbtNtries></TxsSummry>",
+            credits
+        )
+    }
+
+    fn summarized(credits: &str) -> Statement {
+        let body = [
+            summary(credits),
+            entry("250.00", "CRDT", "BOOK", "A1", ""),
+            entry("69.50", "DBIT", "BOOK", "A2", ""),
+        ]
+        .concat();
+        camt053(&[statement("S1", 1, 1, 31, &body)]).remove(0)
+    }
+
+    #[test]
+    fn matching_summary() {
+        assert_eq!(check_summary(&summarized("250.00")), Vec::new());
+    }
+
+    #[test]
+    fn summary_mismatch() {
+        assert_eq!(
+            check_summary(&summarized("260.00")),
+            vec![SummaryMismatch {
+                field: "TtlCdtNtries/Sum",
+                expected: "260".to_string(),
+                found: "250".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Entries given more than once, by re-sent statements or by an intraday
+//! camt.052 report overlapping the end of day camt.053 statement.
+
+use crate::model::{Entry, EntryStatus, MessageKind, Statement};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// What two entries were found to share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DuplicateKey {
+    /// `AcctSvcrRef`
+    AccountServicerReference(String),
+    /// `NtryRef`
+    EntryReference(String),
+    /// `EndToEndId` of the single transaction of the entries
+    EndToEndId(String),
+    /// no reference in common, same amount, dates, texts and transactions
+    Content(u64),
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuplicateKey::AccountServicerReference(reference) => {
+                write!(f, "AcctSvcrRef {}", reference)
+            }
+            DuplicateKey::EntryReference(reference) => write!(f, "NtryRef {}", reference),
+            DuplicateKey::EndToEndId(id) => write!(f, "EndToEndId {}", id),
+            DuplicateKey::Content(hash) => write!(f, "content {:016x}", hash),
+        }
+    }
+}
+
+/// An entry already given by another statement, as indices into the
+/// statements given to [`find_duplicates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateEntry {
+    pub statement: usize,
+    pub entry: usize,
+    pub original_statement: usize,
+    pub original_entry: usize,
+    pub key: DuplicateKey,
+}
+
+/// `EndToEndId` of an entry with a single transaction, `NOTPROVIDED` is
+/// what banks put when there is none.
+fn end_to_end_id(entry: &Entry) -> Option<String> {
+    let mut transactions = entry
+        .details
+        .iter()
+        .flat_map(|details| details.transactions.iter());
+    let transaction = transactions.next()?;
+    if transactions.next().is_some() {
+        return None;
+    }
+    transaction
+        .references
+        .end_to_end_id
+        .clone()
+        .filter(|id| id != "NOTPROVIDED")
+}
+
+/// Hash of what describes an entry, the status and references aside.
+fn content_hash(entry: &Entry) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    entry.amount.amount.normalize().hash(&mut hasher);
+    entry.amount.currency.hash(&mut hasher);
+    entry.credit_debit.code().hash(&mut hasher);
+    entry.booking_date.date().hash(&mut hasher);
+    entry.value_date.map(|date| date.date()).hash(&mut hasher);
+    entry.additional_info.hash(&mut hasher);
+    for transaction in entry
+        .details
+        .iter()
+        .flat_map(|details| details.transactions.iter())
+    {
+        transaction.amount.amount.normalize().hash(&mut hasher);
+        transaction.credit_debit.code().hash(&mut hasher);
+        transaction.references.end_to_end_id.hash(&mut hasher);
+        if let Some(remittance_information) = &transaction.remittance_information {
+            remittance_information.unstructured.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// The first key both entries have, `AcctSvcrRef`, `NtryRef`, then
+/// `EndToEndId`, decides. Without any, the content is compared.
+fn duplicate_key(entry: &Entry, original: &Entry) -> Option<DuplicateKey> {
+    if entry.amount != original.amount || entry.credit_debit != original.credit_debit {
+        return None;
+    }
+    let keys = [
+        (
+            entry.account_servicer_reference.clone(),
+            original.account_servicer_reference.clone(),
+            DuplicateKey::AccountServicerReference as fn(String) -> DuplicateKey,
+        ),
+        (
+            entry.reference.clone(),
+            original.reference.clone(),
+            DuplicateKey::EntryReference,
+        ),
+        (
+            end_to_end_id(entry),
+            end_to_end_id(original),
+            DuplicateKey::EndToEndId,
+        ),
+    ];
+    for (key, original_key, make_key) in keys {
+        if let (Some(key), Some(original_key)) = (key, original_key) {
+            return (key == original_key).then(|| make_key(key));
+        }
+    }
+    let hash = content_hash(entry);
+    (hash == content_hash(original)).then_some(DuplicateKey::Content(hash))
+}
+
+/// Find the entries of each account given again by another statement.
+/// Entries are only compared across statements, two equal entries in one
+/// statement are two transactions. Booked entries are kept over pending
+/// or information ones, otherwise the first one given is. Notifications
+/// are only compared with notifications, [`crate::link_notifications`]
+/// matches them with statements.
+pub fn find_duplicates(statements: &[Statement]) -> Vec<DuplicateEntry> {
+    let booked = |entry: &Entry| matches!(entry.status, None | Some(EntryStatus::Booked));
+    let mut order: Vec<(usize, usize)> = Vec::new();
+    for first_booked in [true, false] {
+        for (statement, stmt) in statements.iter().enumerate() {
+            for (entry, ntry) in stmt.entries.iter().enumerate() {
+                if booked(ntry) == first_booked {
+                    order.push((statement, entry));
+                }
+            }
+        }
+    }
+
+    let mut originals: Vec<(usize, usize)> = Vec::new();
+    let mut duplicates = Vec::new();
+    for (statement, entry) in order {
+        let stmt = &statements[statement];
+        let ntry = &stmt.entries[entry];
+        let found = originals
+            .iter()
+            .find_map(|&(original_statement, original_entry)| {
+                let original_stmt = &statements[original_statement];
+                if original_statement == statement
+                    || original_stmt.account.identifier() != stmt.account.identifier()
+                    || (original_stmt.kind == MessageKind::Notification)
+                        != (stmt.kind == MessageKind::Notification)
+                {
+                    return None;
+                }
+                duplicate_key(ntry, &original_stmt.entries[original_entry])
+                    .map(|key| (original_statement, original_entry, key))
+            });
+        match found {
+            Some((original_statement, original_entry, key)) => duplicates.push(DuplicateEntry {
+                statement,
+                entry,
+                original_statement,
+                original_entry,
+                key,
+            }),
+            None => originals.push((statement, entry)),
+        }
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{camt052, camt053, entry, statement};
+
+    #[test]
+    fn intraday_report_overlapping_statement() {
+        let mut statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &[
+                entry("100.00", "CRDT", "BOOK", "A1", ""),
+                entry("50.00", "DBIT", "BOOK", "A2", ""),
+            ]
+            .concat(),
+        )]);
+        // the same account, written with spaces and its currency
+        let report = statement(
+            "R1",
+            1,
+            31,
+            31,
+            &[
+                entry("100.00", "CRDT", "BOOK", "A1", ""),
+                entry("75.00", "CRDT", "PDNG", "A3", ""),
+            ]
+            .concat(),
+        )
+        .replace(
+            "<IBAN>CH9300762011623852957</IBAN></Id>",
+            "<IBAN>CH93 0076 2011 6238 5295 7</IBAN></Id><Ccy>CHF</Ccy>",
+        );
+        statements.extend(camt052(&[report]));
+
+        assert_eq!(
+            find_duplicates(&statements),
+            vec![DuplicateEntry {
+                statement: 1,
+                entry: 0,
+                original_statement: 0,
+                original_entry: 0,
+                key: DuplicateKey::AccountServicerReference("A1".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn booked_entry_kept_over_pending() {
+        let statements = camt052(&[
+            statement("R1", 1, 2, 2, &entry("100.00", "CRDT", "PDNG", "A1", "")),
+            statement("R2", 2, 3, 3, &entry("100.00", "CRDT", "BOOK", "A1", "")),
+        ]);
+        let duplicates = find_duplicates(&statements);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(
+            (duplicates[0].statement, duplicates[0].original_statement),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn equal_entries_of_one_statement_are_kept() {
+        let statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &[
+                entry("100.00", "CRDT", "BOOK", "A1", ""),
+                entry("100.00", "CRDT", "BOOK", "A1", ""),
+            ]
+            .concat(),
+        )]);
+        assert_eq!(find_duplicates(&statements), Vec::new());
+    }
+
+    #[test]
+    fn different_amount_is_no_duplicate() {
+        let statements = camt053(&[
+            statement("S1", 1, 1, 10, &entry("100.00", "CRDT", "BOOK", "A1", "")),
+            statement("S2", 2, 11, 20, &entry("90.00", "CRDT", "BOOK", "A1", "")),
+        ]);
+        assert_eq!(find_duplicates(&statements), Vec::new());
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+/// Where in the input a problem was found.
+///
+/// `path` is an XPath-like element path such as
+/// `Document/BkToCstmrStmt/Stmt/Ntry[3]/Amt`, `entry` the 1-based index of
+/// the `Ntry` involved, if any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Location {
+    pub file: Option<String>,
+    pub path: String,
+    pub entry: Option<usize>,
+}
+
+impl Location {
+    pub fn new(path: &str) -> Location {
+        Location {
+            file: None,
+            path: path.to_string(),
+            entry: None,
+        }
+    }
+
+    /// Location of an element below this one, `rel` may contain several steps.
+    pub fn join(&self, rel: &str) -> Location {
+        Location {
+            file: self.file.clone(),
+            path: format!("{}/{}", self.path, rel),
+            entry: self.entry,
+        }
+    }
+
+    /// Location of the `index`th (1-based) `Ntry` below this one.
+    pub fn entry(&self, index: usize) -> Location {
+        let mut location = self.join(&format!("Ntry[{}]", index));
+        location.entry = Some(index);
+        location
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) if self.path.is_empty() => write!(f, "{}", file)?,
+            Some(file) => write!(f, "{}: {}", file, self.path)?,
+            None => write!(f, "{}", self.path)?,
+        }
+        if let Some(entry) = self.entry {
+            write!(f, " (entry {})", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything that can go wrong while reading a CAMT file.
+#[derive(Debug)]
+pub enum CamtError {
+    /// the file could not be read
+    Io {
+        location: Location,
+        source: std::io::Error,
+    },
+    /// the file is not well formed XML
+    Xml {
+        location: Location,
+        source: minidom::Error,
+    },
+    /// a mandatory element is absent
+    MissingElement { location: Location },
+    /// an amount is not a valid decimal number
+    BadAmount { value: String, location: Location },
+    /// a date or datetime cannot be parsed
+    BadDate { value: String, location: Location },
+    /// a counter or sequence number is not a valid number
+    BadNumber { value: String, location: Location },
+    /// a code element holds a value outside its code list
+    BadCode { value: String, location: Location },
+    /// the document is not a message this parser understands
+    UnsupportedMessage { message: String, location: Location },
+}
+
+impl CamtError {
+    pub fn location(&self) -> &Location {
+        match self {
+            CamtError::Io { location, .. }
+            | CamtError::Xml { location, .. }
+            | CamtError::MissingElement { location }
+            | CamtError::BadAmount { location, .. }
+            | CamtError::BadDate { location, .. }
+            | CamtError::BadNumber { location, .. }
+            | CamtError::BadCode { location, .. }
+            | CamtError::UnsupportedMessage { location, .. } => location,
+        }
+    }
+
+    pub(crate) fn location_mut(&mut self) -> &mut Location {
+        match self {
+            CamtError::Io { location, .. }
+            | CamtError::Xml { location, .. }
+            | CamtError::MissingElement { location }
+            | CamtError::BadAmount { location, .. }
+            | CamtError::BadDate { location, .. }
+            | CamtError::BadNumber { location, .. }
+            | CamtError::BadCode { location, .. }
+            | CamtError::UnsupportedMessage { location, .. } => location,
+        }
+    }
+
+    /// Attach the name of the file being parsed.
+    pub fn with_file(mut self, file: &str) -> CamtError {
+        self.location_mut().file = Some(file.to_string());
+        self
+    }
+}
+
+impl fmt::Display for CamtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CamtError::Io { location, source } => {
+                write!(f, "{}: cannot read: {}", location, source)
+            }
+            CamtError::Xml { location, source } => {
+                write!(f, "{}: invalid XML: {}", location, source)
+            }
+            CamtError::MissingElement { location } => write!(f, "{}: missing element", location),
+            CamtError::BadAmount { value, location } => {
+                write!(f, "{}: bad amount {:?}", location, value)
+            }
+            CamtError::BadDate { value, location } => {
+                write!(f, "{}: bad date {:?}", location, value)
+            }
+            CamtError::BadNumber { value, location } => {
+                write!(f, "{}: bad number {:?}", location, value)
+            }
+            CamtError::BadCode { value, location } => {
+                write!(f, "{}: unknown code {:?}", location, value)
+            }
+            CamtError::UnsupportedMessage { message, location } => {
+                write!(f, "{}: unsupported message {}", location, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CamtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CamtError::Io { source, .. } => Some(source),
+            CamtError::Xml { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// An entry or transaction that was skipped in lenient mode.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub error: CamtError,
+    /// the raw XML of the skipped `Ntry` or `TxDtls`
+    pub fragment: String,
+}
+
+/// Decides what happens with errors found inside an entry: abort the
+/// statement, or record them and carry on with the next element.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub lenient: bool,
+    pub skipped: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(lenient: bool) -> Diagnostics {
+        Diagnostics {
+            lenient,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Pass `result` through, except in lenient mode where an error is
+    /// recorded together with `element` and `Ok(None)` is returned.
+    pub fn recover<T>(
+        &mut self,
+        result: Result<T, CamtError>,
+        element: &minidom::Element,
+    ) -> Result<Option<T>, CamtError> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(error) if self.lenient => {
+                let mut fragment = Vec::new();
+                // a fragment that cannot be written back is still worth reporting
+                let _ = element.write_to(&mut fragment);
+                self.skipped.push(Diagnostic {
+                    error,
+                    fragment: String::from_utf8_lossy(&fragment).into_owned(),
+                });
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
@@ -0,0 +1,927 @@
+//! Flat CSV projection of the typed model, one line per transaction.
+
+use crate::model::{
+    Account, AmountDetails, BankTransactionCode, Charge, CreditDebit, DateAndDateTime, Entry,
+    FinancialInstitution, Party, References, RelatedAgents, RelatedParties, Statement,
+    TransactionDetails,
+};
+use crate::money::Money;
+use crate::reference::format_iban;
+use chrono::NaiveDateTime;
+use csv::WriterBuilder;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// The date of an entry written in the date column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateColumn {
+    /// `BookgDt`
+    #[default]
+    Booking,
+    /// `ValDt`, or `BookgDt` for entries without one
+    Value,
+}
+
+/// How IBANs are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IbanFormat {
+    /// without spaces, `CH9300762011623852957`
+    #[default]
+    Electronic,
+    /// in groups of four, `CH93 0076 2011 6238 5295 7`
+    Print,
+}
+
+/// How amounts are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmountColumns {
+    /// a `debit` and a `credit` column, the other side `0` or empty
+    #[default]
+    Split,
+    /// a single `amount` column, negative for debits
+    Signed,
+    /// the `amount` without sign and a `direction` column, `credit` or `debit`
+    Direction,
+}
+
+/// How the model is turned into records.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// `strftime` like format of the date column, e.g. `%d.%m.%Y`
+    pub date_format: String,
+    /// which date goes in the date column
+    pub date_column: DateColumn,
+    /// how IBANs are written, other account identifiers are left as they are
+    pub iban_format: IbanFormat,
+    /// which amount columns are written
+    pub amount_columns: AmountColumns,
+    /// leave the zero side of split amounts empty instead of `0`
+    pub empty_zero: bool,
+    /// add the entry and transaction reference columns
+    pub references: bool,
+    /// add the bank transaction code and its label
+    pub transaction_codes: bool,
+    /// put included charges on lines of their own
+    pub charge_lines: bool,
+    /// add the foreign currency amount and exchange rate columns
+    pub exchange: bool,
+    /// add the related parties and agents columns
+    pub parties: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            date_format: "%Y-%m-%d".to_string(),
+            date_column: DateColumn::Booking,
+            iban_format: IbanFormat::Electronic,
+            amount_columns: AmountColumns::Split,
+            empty_zero: false,
+            references: false,
+            transaction_codes: false,
+            charge_lines: false,
+            exchange: false,
+            parties: false,
+        }
+    }
+}
+
+impl ExportOptions {
+    fn format_date(&self, date: &DateAndDateTime) -> String {
+        date.date_time().format(&self.date_format).to_string()
+    }
+
+    /// The identifier of an account, IBANs in the chosen format.
+    pub fn format_account(&self, account: &Account) -> Option<String> {
+        let identifier = account.identifier()?;
+        match self.iban_format {
+            IbanFormat::Print if account.iban.is_some() => Some(format_iban(&identifier)),
+            _ => Some(identifier),
+        }
+    }
+}
+
+/// Check a date format before use, chrono panics on invalid ones and on
+/// timezone specifiers, which naive dates cannot fill.
+pub fn check_date_format(format: &str) -> Result<(), String> {
+    let sample = NaiveDateTime::default();
+    let mut formatted = String::new();
+    write!(formatted, "{}", sample.format(format))
+        .map_err(|_| format!("invalid date format {:?}", format))
+}
+
+// Entry (NTry)
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+pub struct Ntry {
+    pub account: String,     // Account
+    pub date: String,        // date
+    pub description: String, //description of transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debit: Option<String>, // debit amount, with AmountColumns::Split
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credit: Option<String>, // credit amount, with AmountColumns::Split
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>, // signed amount, or without sign with a direction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<String>, // credit or debit, with AmountColumns::Direction
+    pub ntry_type: String,   // type of entry
+    pub currency: String,    // ISO currency of the amount
+    pub status: String,      // BOOK, or PDNG for pending entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<String>, // what it duplicates, only with --duplicates flag
+    // references, only with ExportOptions::references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_reference: Option<String>, // NtryRef
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_account_servicer_reference: Option<String>, // Ntry/AcctSvcrRef
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>, // Refs/MsgId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_servicer_reference: Option<String>, // Refs/AcctSvcrRef
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_information_id: Option<String>, // Refs/PmtInfId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_id: Option<String>, // Refs/InstrId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_to_end_id: Option<String>, // Refs/EndToEndId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>, // Refs/TxId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mandate_id: Option<String>, // Refs/MndtId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cheque_number: Option<String>, // Refs/ChqNb
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clearing_system_reference: Option<String>, // Refs/ClrSysRef
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_reference: Option<String>, // RmtInf/Strd/CdtrRefInf/Ref, e.g. QR-reference
+    // bank transaction code, only with ExportOptions::transaction_codes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_code: Option<String>, // BkTxCd, e.g. PMNT/RCDT/ESCT
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction_label: Option<String>, // readable BkTxCd
+    // foreign currency, only with ExportOptions::exchange
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_amount: Option<String>, // AmtDtls amount not in the booked currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_currency: Option<String>, // its currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<String>, // CcyXchg/XchgRate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_currency: Option<String>, // CcyXchg/SrcCcy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_currency: Option<String>, // CcyXchg/TrgtCcy
+    // related parties, only with ExportOptions::parties
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debtor_name: Option<String>, // Dbtr/Nm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debtor_address: Option<String>, // Dbtr/PstlAdr on one line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debtor_id: Option<String>, // Dbtr/Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ultimate_debtor_name: Option<String>, // UltmtDbtr/Nm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debtor_agent: Option<String>, // DbtrAgt BIC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_name: Option<String>, // Cdtr/Nm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_address: Option<String>, // Cdtr/PstlAdr on one line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_id: Option<String>, // Cdtr/Id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ultimate_creditor_name: Option<String>, // UltmtCdtr/Nm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_agent: Option<String>, // CdtrAgt BIC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debtor_account: Option<String>, // DbtrAcct, IBAN or other id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creditor_account: Option<String>, // CdtrAcct, IBAN or other id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty_account: Option<String>, // DbtrAcct of a credit, CdtrAcct of a debit
+}
+
+impl Ntry {
+    /// Names of the columns, in the order they are written by [`write_csv`].
+    pub const FIELDS: [&'static str; 43] = [
+        "account",
+        "date",
+        "description",
+        "debit",
+        "credit",
+        "amount",
+        "direction",
+        "ntry_type",
+        "currency",
+        "status",
+        "duplicate",
+        "entry_reference",
+        "entry_account_servicer_reference",
+        "message_id",
+        "account_servicer_reference",
+        "payment_information_id",
+        "instruction_id",
+        "end_to_end_id",
+        "transaction_id",
+        "mandate_id",
+        "cheque_number",
+        "clearing_system_reference",
+        "creditor_reference",
+        "transaction_code",
+        "transaction_label",
+        "original_amount",
+        "original_currency",
+        "exchange_rate",
+        "source_currency",
+        "target_currency",
+        "debtor_name",
+        "debtor_address",
+        "debtor_id",
+        "ultimate_debtor_name",
+        "debtor_agent",
+        "creditor_name",
+        "creditor_address",
+        "creditor_id",
+        "ultimate_creditor_name",
+        "creditor_agent",
+        "debtor_account",
+        "creditor_account",
+        "counterparty_account",
+    ];
+
+    /// Value of a column by name, `None` for columns not exported and
+    /// unknown names.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        match name {
+            "account" => Some(self.account.as_str()),
+            "date" => Some(self.date.as_str()),
+            "description" => Some(self.description.as_str()),
+            "debit" => self.debit.as_deref(),
+            "credit" => self.credit.as_deref(),
+            "amount" => self.amount.as_deref(),
+            "direction" => self.direction.as_deref(),
+            "ntry_type" => Some(self.ntry_type.as_str()),
+            "currency" => Some(self.currency.as_str()),
+            "status" => Some(self.status.as_str()),
+            "duplicate" => self.duplicate.as_deref(),
+            "entry_reference" => self.entry_reference.as_deref(),
+            "entry_account_servicer_reference" => self.entry_account_servicer_reference.as_deref(),
+            "message_id" => self.message_id.as_deref(),
+            "account_servicer_reference" => self.account_servicer_reference.as_deref(),
+            "payment_information_id" => self.payment_information_id.as_deref(),
+            "instruction_id" => self.instruction_id.as_deref(),
+            "end_to_end_id" => self.end_to_end_id.as_deref(),
+            "transaction_id" => self.transaction_id.as_deref(),
+            "mandate_id" => self.mandate_id.as_deref(),
+            "cheque_number" => self.cheque_number.as_deref(),
+            "clearing_system_reference" => self.clearing_system_reference.as_deref(),
+            "creditor_reference" => self.creditor_reference.as_deref(),
+            "transaction_code" => self.transaction_code.as_deref(),
+            "transaction_label" => self.transaction_label.as_deref(),
+            "original_amount" => self.original_amount.as_deref(),
+            "original_currency" => self.original_currency.as_deref(),
+            "exchange_rate" => self.exchange_rate.as_deref(),
+            "source_currency" => self.source_currency.as_deref(),
+            "target_currency" => self.target_currency.as_deref(),
+            "debtor_name" => self.debtor_name.as_deref(),
+            "debtor_address" => self.debtor_address.as_deref(),
+            "debtor_id" => self.debtor_id.as_deref(),
+            "ultimate_debtor_name" => self.ultimate_debtor_name.as_deref(),
+            "debtor_agent" => self.debtor_agent.as_deref(),
+            "creditor_name" => self.creditor_name.as_deref(),
+            "creditor_address" => self.creditor_address.as_deref(),
+            "creditor_id" => self.creditor_id.as_deref(),
+            "ultimate_creditor_name" => self.ultimate_creditor_name.as_deref(),
+            "creditor_agent" => self.creditor_agent.as_deref(),
+            "debtor_account" => self.debtor_account.as_deref(),
+            "creditor_account" => self.creditor_account.as_deref(),
+            "counterparty_account" => self.counterparty_account.as_deref(),
+            _ => None,
+        }
+    }
+
+    // push amount in the amount columns of the options
+    fn set_amount(&mut self, amount: &Money, credit_debit: CreditDebit, options: &ExportOptions) {
+        match options.amount_columns {
+            AmountColumns::Split => {
+                let zero = if options.empty_zero { "" } else { "0" };
+                self.debit = Some(zero.to_string());
+                self.credit = Some(zero.to_string());
+                match credit_debit {
+                    CreditDebit::Credit => self.credit = Some(amount.amount_string()),
+                    CreditDebit::Debit => self.debit = Some(amount.amount_string()),
+                }
+            }
+            AmountColumns::Signed => {
+                let signed = Money {
+                    amount: amount.signed(credit_debit),
+                    currency: amount.currency.clone(),
+                };
+                self.amount = Some(signed.amount_string());
+            }
+            AmountColumns::Direction => {
+                self.amount = Some(amount.amount_string());
+                self.direction = Some(
+                    match credit_debit {
+                        CreditDebit::Credit => "credit",
+                        CreditDebit::Debit => "debit",
+                    }
+                    .to_string(),
+                );
+            }
+        }
+        self.currency = amount.currency.to_string();
+    }
+
+    // fill the foreign currency columns, when they are exported
+    fn set_exchange(&mut self, details: &AmountDetails, booked: &Money, options: &ExportOptions) {
+        if !options.exchange {
+            return;
+        }
+        let foreign = details.foreign_amount(&booked.currency);
+        self.original_amount = Some(
+            foreign
+                .map(|foreign| foreign.amount.amount_string())
+                .unwrap_or_default(),
+        );
+        self.original_currency = Some(
+            foreign
+                .map(|foreign| foreign.amount.currency.to_string())
+                .unwrap_or_default(),
+        );
+        let exchange = details.exchange();
+        self.exchange_rate = Some(
+            exchange
+                .map(|exchange| exchange.rate.to_string())
+                .unwrap_or_default(),
+        );
+        self.source_currency = Some(
+            exchange
+                .map(|exchange| exchange.source_currency.to_string())
+                .unwrap_or_default(),
+        );
+        self.target_currency = Some(
+            exchange
+                .and_then(|exchange| exchange.target_currency.as_ref())
+                .map(|currency| currency.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    // fill the related parties columns, when they are exported
+    fn set_parties(
+        &mut self,
+        parties: Option<&RelatedParties>,
+        agents: Option<&RelatedAgents>,
+        credit_debit: CreditDebit,
+        options: &ExportOptions,
+    ) {
+        if !options.parties {
+            return;
+        }
+        let name = |party: Option<&Party>| party.and_then(|party| party.name.clone());
+        let address = |party: Option<&Party>| {
+            party
+                .and_then(|party| party.postal_address.as_ref())
+                .map(|address| address.one_line())
+        };
+        let id = |party: Option<&Party>| {
+            party
+                .and_then(|party| party.identification.as_ref())
+                .and_then(|identification| identification.summary())
+        };
+        let bic = |agent: Option<&FinancialInstitution>| agent.and_then(|agent| agent.bic.clone());
+        let column = |value: Option<String>| Some(value.unwrap_or_default());
+
+        let debtor = parties.and_then(|parties| parties.debtor.as_ref());
+        let creditor = parties.and_then(|parties| parties.creditor.as_ref());
+        self.debtor_name = column(name(debtor));
+        self.debtor_address = column(address(debtor));
+        self.debtor_id = column(id(debtor));
+        self.ultimate_debtor_name = column(name(
+            parties.and_then(|parties| parties.ultimate_debtor.as_ref()),
+        ));
+        self.debtor_agent = column(bic(agents.and_then(|agents| agents.debtor_agent.as_ref())));
+        self.creditor_name = column(name(creditor));
+        self.creditor_address = column(address(creditor));
+        self.creditor_id = column(id(creditor));
+        self.ultimate_creditor_name = column(name(
+            parties.and_then(|parties| parties.ultimate_creditor.as_ref()),
+        ));
+        self.creditor_agent = column(bic(agents.and_then(|agents| agents.creditor_agent.as_ref())));
+
+        let debtor_account = parties
+            .and_then(|parties| parties.debtor_account.as_ref())
+            .and_then(|account| options.format_account(account));
+        let creditor_account = parties
+            .and_then(|parties| parties.creditor_account.as_ref())
+            .and_then(|account| options.format_account(account));
+        // the other side of the transaction, the account on ours is the
+        // statement account
+        let counterparty_account = match credit_debit {
+            CreditDebit::Credit => debtor_account.clone(),
+            CreditDebit::Debit => creditor_account.clone(),
+        };
+        self.debtor_account = column(debtor_account);
+        self.creditor_account = column(creditor_account);
+        self.counterparty_account = column(counterparty_account);
+    }
+
+    // fill the transaction code columns, when they are exported
+    fn set_transaction_code(
+        &mut self,
+        code: Option<&BankTransactionCode>,
+        options: &ExportOptions,
+    ) {
+        if !options.transaction_codes {
+            return;
+        }
+        self.transaction_code = Some(code.and_then(|code| code.code()).unwrap_or_default());
+        self.transaction_label = Some(code.and_then(|code| code.label()).unwrap_or_default());
+    }
+
+    // fill the transaction reference columns, when they are exported
+    fn set_references(&mut self, references: &References, options: &ExportOptions) {
+        if !options.references {
+            return;
+        }
+        let column = |reference: &Option<String>| Some(reference.clone().unwrap_or_default());
+        self.message_id = column(&references.message_id);
+        self.account_servicer_reference = column(&references.account_servicer_reference);
+        self.payment_information_id = column(&references.payment_information_id);
+        self.instruction_id = column(&references.instruction_id);
+        self.end_to_end_id = column(&references.end_to_end_id);
+        self.transaction_id = column(&references.transaction_id);
+        self.mandate_id = column(&references.mandate_id);
+        self.cheque_number = column(&references.cheque_number);
+        self.clearing_system_reference = column(&references.clearing_system_reference);
+        self.creditor_reference = Some(String::new());
+    }
+}
+
+/// Records of all entries of a statement.
+pub fn statement_rows(statement: &Statement, options: &ExportOptions) -> Vec<Ntry> {
+    let account = options
+        .format_account(&statement.account)
+        .unwrap_or_default();
+    statement
+        .entries
+        .iter()
+        .flat_map(|entry| entry_rows(&account, entry, options))
+        .collect()
+}
+
+/// Records of one entry, one per `TxDtls`, or the entry itself if it has none.
+pub fn entry_rows(account: &str, entry: &Entry, options: &ExportOptions) -> Vec<Ntry> {
+    let mut record = Ntry {
+        account: account.to_string(),
+        date: match options.date_column {
+            DateColumn::Booking => options.format_date(&entry.booking_date),
+            DateColumn::Value => {
+                options.format_date(entry.value_date.as_ref().unwrap_or(&entry.booking_date))
+            }
+        },
+        description: entry.additional_info.clone().unwrap_or_default(),
+        debit: None,
+        credit: None,
+        amount: None,
+        direction: None,
+        ntry_type: entry.credit_debit.code().to_string(),
+        currency: String::new(),
+        status: entry
+            .status
+            .as_ref()
+            .map(|status| status.code().to_string())
+            .unwrap_or_default(),
+        duplicate: None,
+        entry_reference: None,
+        entry_account_servicer_reference: None,
+        message_id: None,
+        account_servicer_reference: None,
+        payment_information_id: None,
+        instruction_id: None,
+        end_to_end_id: None,
+        transaction_id: None,
+        mandate_id: None,
+        cheque_number: None,
+        clearing_system_reference: None,
+        creditor_reference: None,
+        transaction_code: None,
+        transaction_label: None,
+        original_amount: None,
+        original_currency: None,
+        exchange_rate: None,
+        source_currency: None,
+        target_currency: None,
+        debtor_name: None,
+        debtor_address: None,
+        debtor_id: None,
+        ultimate_debtor_name: None,
+        debtor_agent: None,
+        creditor_name: None,
+        creditor_address: None,
+        creditor_id: None,
+        ultimate_creditor_name: None,
+        creditor_agent: None,
+        debtor_account: None,
+        creditor_account: None,
+        counterparty_account: None,
+    };
+    record.set_amount(&entry.amount, entry.credit_debit, options);
+    if options.references {
+        record.entry_reference = Some(entry.reference.clone().unwrap_or_default());
+        record.entry_account_servicer_reference =
+            Some(entry.account_servicer_reference.clone().unwrap_or_default());
+    }
+    record.set_references(&References::default(), options);
+    record.set_transaction_code(entry.bank_transaction_code.as_ref(), options);
+    record.set_parties(None, None, entry.credit_debit, options);
+    record.set_exchange(
+        entry
+            .amount_details
+            .as_ref()
+            .unwrap_or(&AmountDetails::default()),
+        &entry.amount,
+        options,
+    );
+
+    let transactions: Vec<&TransactionDetails> = entry
+        .details
+        .iter()
+        .flat_map(|details| details.transactions.iter())
+        .collect();
+    // banks often repeat the charges of the transactions on the entry
+    let transaction_charges = transactions.iter().any(|tx| !tx.charges.is_empty());
+
+    let mut result = Vec::new();
+    for tx in &transactions {
+        let mut row = transaction_row(&record, tx, options);
+        if options.charge_lines && transaction_charges {
+            let charges = charge_rows(&mut row, &tx.amount, tx.credit_debit, &tx.charges, options);
+            result.push(row);
+            result.extend(charges);
+        } else {
+            result.push(row);
+        }
+    }
+
+    if result.is_empty() {
+        // the entry line would give the amount of the skipped transactions
+        if entry.skipped_transactions > 0 {
+            return result;
+        }
+        result.push(record);
+    }
+    if options.charge_lines && !transaction_charges {
+        // the charges of the entry come out of its first line
+        let (amount, credit_debit) = match transactions.first() {
+            Some(tx) => (&tx.amount, tx.credit_debit),
+            None => (&entry.amount, entry.credit_debit),
+        };
+        let charges = charge_rows(
+            &mut result[0],
+            amount,
+            credit_debit,
+            &entry.charges,
+            options,
+        );
+        result.extend(charges);
+    }
+    result
+}
+
+/// Lines of the charges included in the amount of `line`, which is
+/// reduced by them, so that the lines still add up to the booked amount.
+/// Charges not included are booked on their own and left out.
+fn charge_rows(
+    line: &mut Ntry,
+    amount: &Money,
+    credit_debit: CreditDebit,
+    charges: &[Charge],
+    options: &ExportOptions,
+) -> Vec<Ntry> {
+    let mut net = amount.signed(credit_debit);
+    let mut rows = Vec::new();
+    for charge in charges
+        .iter()
+        .filter(|charge| charge.included == Some(true))
+    {
+        let mut row = line.clone();
+        row.description = match &charge.charge_type {
+            Some(charge_type) => format!("charges {}", charge_type),
+            None => "charges".to_string(),
+        };
+        let charge_credit_debit = charge.credit_debit.unwrap_or(CreditDebit::Debit);
+        row.ntry_type = charge_credit_debit.code().to_string();
+        row.set_amount(&charge.amount, charge_credit_debit, options);
+        // the foreign amount is the one of the line, not of the charge
+        row.set_exchange(&AmountDetails::default(), &charge.amount, options);
+        if charge.amount.currency == amount.currency {
+            net -= charge.signed_amount();
+        }
+        rows.push(row);
+    }
+
+    let (net_credit_debit, net_amount) = if net.is_sign_negative() {
+        (CreditDebit::Debit, -net)
+    } else {
+        (CreditDebit::Credit, net)
+    };
+    line.ntry_type = net_credit_debit.code().to_string();
+    line.set_amount(
+        &Money {
+            amount: net_amount,
+            currency: amount.currency.clone(),
+        },
+        net_credit_debit,
+        options,
+    );
+    rows
+}
+
+/// Refine an entry record with the details of one transaction.
+fn transaction_row(entry: &Ntry, tx: &TransactionDetails, options: &ExportOptions) -> Ntry {
+    let mut result = entry.clone();
+
+    // corresponding party, a debtor wins over a creditor
+    if let Some(parties) = &tx.related_parties {
+        let mut partner_nm = "unknown_partner".to_string();
+        let mut iban = "unknown_iban".to_string();
+        let name_of = |name: &Option<String>| {
+            name.clone()
+                .unwrap_or_else(|| "unknown_partner".to_string())
+        };
+
+        if let Some(cdtr) = &parties.creditor {
+            partner_nm = name_of(&cdtr.name);
+            iban = match &parties.creditor_account {
+                Some(account) => options
+                    .format_account(account)
+                    .unwrap_or_else(|| "Not Found".to_string()),
+                None => "no IBAN".to_string(),
+            };
+        }
+
+        if let Some(dbtr) = &parties.debtor {
+            partner_nm = name_of(&dbtr.name);
+            iban = match &parties.debtor_account {
+                Some(account) => options
+                    .format_account(account)
+                    .unwrap_or_else(|| "Not Found".to_string()),
+                None => "UKNOWN IBAN".to_string(),
+            };
+        }
+
+        result.description = format!("{} - {}", partner_nm, iban);
+    }
+
+    // Remote Information / Ustrd
+    if let Some(rmt_inf) = &tx.remittance_information {
+        result.description.push_str(&rmt_inf.unstructured.join(" "));
+    }
+
+    result.set_amount(&tx.amount, tx.credit_debit, options);
+    result.set_references(&tx.references, options);
+    result.set_parties(
+        tx.related_parties.as_ref(),
+        tx.related_agents.as_ref(),
+        tx.credit_debit,
+        options,
+    );
+    if let Some(details) = &tx.amount_details {
+        result.set_exchange(details, &tx.amount, options);
+    }
+    // the code of the transaction is more precise than the one of the entry
+    if tx.bank_transaction_code.is_some() {
+        result.set_transaction_code(tx.bank_transaction_code.as_ref(), options);
+    }
+    if options.references {
+        let reference = tx
+            .remittance_information
+            .as_ref()
+            .and_then(|rmt_inf| rmt_inf.creditor_reference());
+        result.creditor_reference = Some(
+            reference
+                .map(|reference| reference.reference.clone())
+                .unwrap_or_default(),
+        );
+    }
+    result
+}
+
+/// Write entries as `;` separated CSV, with a header line.
+pub fn write_csv<W: Write>(writer: W, ntry_vec: &[Ntry]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = WriterBuilder::new().delimiter(b';').from_writer(writer);
+    for record in ntry_vec {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{camt053, entry, message, parse_lenient, statement};
+    use rust_decimal::Decimal;
+
+    const CHARGE: &str = "<Chrgs><Rcrd><Amt Ccy=\"CHF\">1.50</Amt><CdtDbtInd>DBIT</CdtDbtInd>\
+                          <ChrgInclInd>true</ChrgInclInd><Tp><Cd>COMM</Cd></Tp></Rcrd>\
+                          <Rcrd><Amt Ccy=\"CHF\">2.00</Amt><CdtDbtInd>DBIT</CdtDbtInd>\
+                          <ChrgInclInd>false</ChrgInclInd></Rcrd></Chrgs>";
+
+    fn rows(extra: &str) -> Vec<Ntry> {
+        let statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &entry("69.50", "DBIT", "BOOK", "A1", extra),
+        )]);
+        let options = ExportOptions {
+            amount_columns: AmountColumns::Signed,
+            charge_lines: true,
+            ..ExportOptions::default()
+        };
+        entry_rows("CH9300762011623852957", &statements[0].entries[0], &options)
+    }
+
+    fn amounts(rows: &[Ntry]) -> Vec<Decimal> {
+        rows.iter()
+            .map(|row| row.amount.as_deref().unwrap().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn entry_charge_lines_add_up() {
+        let rows = rows(CHARGE);
+        assert_eq!(
+            amounts(&rows),
+            vec![Decimal::new(-6800, 2), Decimal::new(-150, 2)]
+        );
+        assert_eq!(rows[1].description, "charges COMM");
+        assert_eq!(
+            amounts(&rows).iter().sum::<Decimal>(),
+            Decimal::new(-6950, 2)
+        );
+    }
+
+    #[test]
+    fn transaction_charge_lines_add_up() {
+        let details = format!(
+            "<NtryDtls><TxDtls><Amt Ccy=\"CHF\">69.50</Amt><CdtDbtInd>DBIT</CdtDbtInd>{}\
+             </TxDtls></NtryDtls>",
+            CHARGE
+        );
+        let rows = rows(&details);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            amounts(&rows).iter().sum::<Decimal>(),
+            Decimal::new(-6950, 2)
+        );
+    }
+
+    #[test]
+    fn reference_columns() {
+        let details = "<NtryDtls><TxDtls><Refs><AcctSvcrRef>T1</AcctSvcrRef>\
+                       <EndToEndId>E2E-1</EndToEndId></Refs>\
+                       <Amt Ccy=\"CHF\">50.00</Amt><CdtDbtInd>CRDT</CdtDbtInd>\
+                       <RmtInf><Strd><CdtrRefInf><Tp><CdOrPrtry><Prtry>QRR</Prtry></CdOrPrtry></Tp>\
+                       <Ref>210000000003139471430009017</Ref></CdtrRefInf></Strd></RmtInf>\
+                       </TxDtls></NtryDtls>";
+        let statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &[
+                entry("50.00", "CRDT", "BOOK", "A1", details),
+                entry("10.00", "DBIT", "BOOK", "A2", ""),
+            ]
+            .concat(),
+        )]);
+        let options = ExportOptions {
+            references: true,
+            ..ExportOptions::default()
+        };
+        let rows = statement_rows(&statements[0], &options);
+        assert_eq!(
+            rows[0].entry_account_servicer_reference.as_deref(),
+            Some("A1")
+        );
+        assert_eq!(rows[0].account_servicer_reference.as_deref(), Some("T1"));
+        assert_eq!(rows[0].end_to_end_id.as_deref(), Some("E2E-1"));
+        assert_eq!(
+            rows[0].creditor_reference.as_deref(),
+            Some("210000000003139471430009017")
+        );
+        // an entry without details has only its own references
+        assert_eq!(
+            rows[1].entry_account_servicer_reference.as_deref(),
+            Some("A2")
+        );
+        assert_eq!(rows[1].account_servicer_reference.as_deref(), Some(""));
+        assert_eq!(rows[1].creditor_reference.as_deref(), Some(""));
+
+        let rows = statement_rows(&statements[0], &ExportOptions::default());
+        assert_eq!(rows[0].entry_account_servicer_reference, None);
+        assert_eq!(rows[0].creditor_reference, None);
+    }
+
+    fn parties_row(credit_debit: &str, parties: &str) -> Ntry {
+        let details = format!(
+            "<NtryDtls><TxDtls><Amt Ccy=\"CHF\">50.00</Amt><CdtDbtInd>{}</CdtDbtInd>\
+             <RltdPties>{}</RltdPties></TxDtls></NtryDtls>",
+            credit_debit, parties
+        );
+        let statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &entry("50.00", credit_debit, "BOOK", "A1", &details),
+        )]);
+        let options = ExportOptions {
+            parties: true,
+            ..ExportOptions::default()
+        };
+        entry_rows("", &statements[0].entries[0], &options).remove(0)
+    }
+
+    fn lenient_rows(details: &str) -> (usize, Vec<Ntry>) {
+        let xml = message(
+            "camt.053.001.04",
+            "",
+            &[statement(
+                "S1",
+                1,
+                1,
+                31,
+                &entry(
+                    "50.00",
+                    "CRDT",
+                    "BOOK",
+                    "A1",
+                    &format!("<NtryDtls>{}</NtryDtls>", details),
+                ),
+            )],
+        );
+        let document = parse_lenient(&xml);
+        let rows = entry_rows(
+            "",
+            &document.statements[0].entries[0],
+            &ExportOptions::default(),
+        );
+        (document.diagnostics.len(), rows)
+    }
+
+    #[test]
+    fn only_transaction_skipped() {
+        let (skipped, rows) =
+            lenient_rows("<TxDtls><Amt Ccy=\"CHF\">5x</Amt><CdtDbtInd>CRDT</CdtDbtInd></TxDtls>");
+        assert_eq!(skipped, 1);
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn one_of_two_transactions_skipped() {
+        let (skipped, rows) = lenient_rows(
+            "<TxDtls><Amt Ccy=\"CHF\">5x</Amt><CdtDbtInd>CRDT</CdtDbtInd></TxDtls>\
+             <TxDtls><Amt Ccy=\"CHF\">20.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></TxDtls>",
+        );
+        assert_eq!(skipped, 1);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].credit.as_deref(), Some("20.00"));
+    }
+
+    #[test]
+    fn counterparty_account() {
+        let debtor = "<Dbtr><Nm>John Doe</Nm></Dbtr>\
+                      <DbtrAcct><Id><IBAN>CH5604835012345678009</IBAN></Id></DbtrAcct>";
+        let creditor = "<Cdtr><Nm>ACME SA</Nm></Cdtr>\
+                        <CdtrAcct><Id><IBAN>CH9300762011623852957</IBAN></Id></CdtrAcct>";
+        let row = parties_row("CRDT", &format!("{}{}", debtor, creditor));
+        assert_eq!(
+            row.counterparty_account.as_deref(),
+            Some("CH5604835012345678009")
+        );
+        let row = parties_row("DBIT", &format!("{}{}", debtor, creditor));
+        assert_eq!(
+            row.counterparty_account.as_deref(),
+            Some("CH9300762011623852957")
+        );
+    }
+
+    #[test]
+    fn credit_without_debtor_account() {
+        // only our own account is known, the counterparty's stays empty
+        let row = parties_row(
+            "CRDT",
+            "<Cdtr><Nm>ACME SA</Nm></Cdtr>\
+             <CdtrAcct><Id><IBAN>CH9300762011623852957</IBAN></Id></CdtrAcct>",
+        );
+        assert_eq!(
+            row.creditor_account.as_deref(),
+            Some("CH9300762011623852957")
+        );
+        assert_eq!(row.counterparty_account.as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,100 @@
+//! Small camt documents for the unit tests.
+
+use crate::model::{Document, Statement};
+use crate::ParseOptions;
+
+/// A booked or pending entry of `amount` CHF, `CRDT` or `DBIT`, with an
+/// `AcctSvcrRef` and whatever `extra` elements are given.
+pub(crate) fn entry(
+    amount: &str,
+    credit_debit: &str,
+    status: &str,
+    reference: &str,
+    extra: &str,
+) -> String {
+    format!(
+        "<Ntry><Amt Ccy=\"CHF\">{}</Amt><CdtDbtInd>{}</CdtDbtInd><Sts>{}</Sts>\
+         <BookgDt><Dt>2023-05-02</Dt></BookgDt><AcctSvcrRef>{}</AcctSvcrRef>{}</Ntry>",
+        amount, credit_debit, status, reference, extra
+    )
+}
+
+/// A `TxDtls` of `amount` CHF, `CRDT` or `DBIT`, with whatever `extra`
+/// elements are given.
+pub(crate) fn transaction(amount: &str, credit_debit: &str, extra: &str) -> String {
+    format!(
+        "<TxDtls><Amt Ccy=\"CHF\">{}</Amt><CdtDbtInd>{}</CdtDbtInd>{}</TxDtls>",
+        amount, credit_debit, extra
+    )
+}
+
+/// `NtryDtls` holding `transactions`, to pass as the `extra` of an entry.
+pub(crate) fn details(transactions: &[String]) -> String {
+    format!("<NtryDtls>{}</NtryDtls>", transactions.concat())
+}
+
+/// A balance of `amount` CHF, e.g. `OPBD` or `CLBD`.
+pub(crate) fn balance(code: &str, amount: &str) -> String {
+    format!(
+        "<Bal><Tp><CdOrPrtry><Cd>{}</Cd></CdOrPrtry></Tp><Amt Ccy=\"CHF\">{}</Amt>\
+         <CdtDbtInd>CRDT</CdtDbtInd><Dt><Dt>2023-05-31</Dt></Dt></Bal>",
+        code, amount
+    )
+}
+
+/// A statement of `CH9300762011623852957` with `ElctrncSeqNb` `sequence`,
+/// covering the days `from` to `to` of May 2023.
+pub(crate) fn statement(id: &str, sequence: u64, from: u32, to: u32, body: &str) -> String {
+    format!(
+        "<Id>{}</Id><ElctrncSeqNb>{}</ElctrncSeqNb><CreDtTm>2023-05-{:02}T20:00:00</CreDtTm>\
+         <FrToDt><FrDtTm>2023-05-{:02}T00:00:00</FrDtTm><ToDtTm>2023-05-{:02}T23:59:59</ToDtTm></FrToDt>\
+         <Acct><Id><IBAN>CH9300762011623852957</IBAN></Id></Acct>{}",
+        id, sequence, to, from, to, body
+    )
+}
+
+/// The statements of a camt.053 message made of `statements`.
+pub(crate) fn camt053(statements: &[String]) -> Vec<Statement> {
+    parse(&message("camt.053.001.04", "", statements)).statements
+}
+
+/// The reports of a camt.052 message made of `reports`.
+pub(crate) fn camt052(reports: &[String]) -> Vec<Statement> {
+    parse(&message("camt.052.001.04", "", reports)).statements
+}
+
+/// The notifications of a camt.054 message made of `notifications`.
+pub(crate) fn camt054(notifications: &[String]) -> Vec<Statement> {
+    parse(&message("camt.054.001.04", "", notifications)).statements
+}
+
+/// A message of `version`, e.g. `camt.054.001.08`, made of `statements`,
+/// the insides of its `Stmt`, `Rpt` or `Ntfctn` elements. `group_header`
+/// goes in `GrpHdr` after `MsgId` and `CreDtTm`.
+pub(crate) fn message(version: &str, group_header: &str, statements: &[String]) -> String {
+    let (message, element) = match &version[..8] {
+        "camt.052" => ("BkToCstmrAcctRpt", "Rpt"),
+        "camt.054" => ("BkToCstmrDbtCdtNtfctn", "Ntfctn"),
+        _ => ("BkToCstmrStmt", "Stmt"),
+    };
+    let statements: String = statements
+        .iter()
+        .map(|statement| format!("<{0}>{1}</{0}>", element, statement))
+        .collect();
+    format!(
+        "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:{}\"><{}>\
+         <GrpHdr><MsgId>MSG</MsgId><CreDtTm>2023-05-31T20:00:00</CreDtTm>{}</GrpHdr>{}</{}></Document>",
+        version, message, group_header, statements, message
+    )
+}
+
+pub(crate) fn parse(xml: &str) -> Document {
+    crate::parse_str(xml).expect("fixture parses")
+}
+
+/// Parse in lenient mode, skipping what is malformed.
+pub(crate) fn parse_lenient(xml: &str) -> Document {
+    ParseOptions { lenient: true }
+        .parse_str(xml)
+        .expect("fixture parses")
+}
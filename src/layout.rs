@@ -0,0 +1,368 @@
+//! CSV layout read from a TOML mapping file: which columns in which order,
+//! their headers, the delimiter, quoting and decimal separator.
+
+use crate::export::{AmountColumns, ExportOptions, Ntry};
+use csv::{QuoteStyle, WriterBuilder};
+use serde::Deserialize;
+use std::io::Write;
+use std::path::Path;
+
+/// Columns written by the `references` export option.
+const REFERENCE_FIELDS: [&str; 12] = [
+    "entry_reference",
+    "entry_account_servicer_reference",
+    "message_id",
+    "account_servicer_reference",
+    "payment_information_id",
+    "instruction_id",
+    "end_to_end_id",
+    "transaction_id",
+    "mandate_id",
+    "cheque_number",
+    "clearing_system_reference",
+    "creditor_reference",
+];
+
+/// Columns written by the `transaction_codes` export option.
+const TRANSACTION_CODE_FIELDS: [&str; 2] = ["transaction_code", "transaction_label"];
+
+/// Columns written by the `exchange` export option.
+const EXCHANGE_FIELDS: [&str; 5] = [
+    "original_amount",
+    "original_currency",
+    "exchange_rate",
+    "source_currency",
+    "target_currency",
+];
+
+/// Columns written by the `parties` export option.
+const PARTY_FIELDS: [&str; 13] = [
+    "debtor_name",
+    "debtor_address",
+    "debtor_id",
+    "ultimate_debtor_name",
+    "debtor_agent",
+    "creditor_name",
+    "creditor_address",
+    "creditor_id",
+    "ultimate_creditor_name",
+    "creditor_agent",
+    "debtor_account",
+    "creditor_account",
+    "counterparty_account",
+];
+
+/// Columns holding a decimal number.
+const DECIMAL_FIELDS: [&str; 5] = [
+    "debit",
+    "credit",
+    "amount",
+    "original_amount",
+    "exchange_rate",
+];
+
+/// A column of the output.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Column {
+    /// one of [`Ntry::FIELDS`]
+    pub field: String,
+    /// header of the column, the field name when not given
+    pub header: Option<String>,
+}
+
+impl Column {
+    pub fn header(&self) -> &str {
+        self.header.as_deref().unwrap_or(&self.field)
+    }
+}
+
+/// Which values are put in quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quoting {
+    Always,
+    /// only values containing the delimiter, a quote or a line break
+    #[default]
+    Necessary,
+    NonNumeric,
+    Never,
+}
+
+impl Quoting {
+    fn quote_style(self) -> QuoteStyle {
+        match self {
+            Quoting::Always => QuoteStyle::Always,
+            Quoting::Necessary => QuoteStyle::Necessary,
+            Quoting::NonNumeric => QuoteStyle::NonNumeric,
+            Quoting::Never => QuoteStyle::Never,
+        }
+    }
+}
+
+/// How the CSV file is written. A mapping file looks like
+///
+/// ```toml
+/// delimiter = ","
+/// decimal_separator = ","
+/// quoting = "always"
+///
+/// [[columns]]
+/// field = "date"
+/// header = "Booking date"
+///
+/// [[columns]]
+/// field = "end_to_end_id"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Layout {
+    /// columns in order, when empty those of the export options
+    pub columns: Vec<Column>,
+    pub delimiter: char,
+    pub quoting: Quoting,
+    /// put in place of `.` in amounts and exchange rates
+    pub decimal_separator: char,
+    /// write a header line
+    pub header: bool,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            columns: Vec::new(),
+            delimiter: ';',
+            quoting: Quoting::default(),
+            decimal_separator: '.',
+            header: true,
+        }
+    }
+}
+
+impl Layout {
+    pub fn from_toml(text: &str) -> Result<Layout, String> {
+        let layout: Layout =
+            toml::from_str(text).map_err(|error| error.to_string().trim_end().to_string())?;
+        if !layout.delimiter.is_ascii() {
+            return Err(format!(
+                "delimiter {:?} is not an ASCII character",
+                layout.delimiter
+            ));
+        }
+        for column in &layout.columns {
+            if !Ntry::FIELDS.contains(&column.field.as_str()) {
+                return Err(format!(
+                    "unknown field {:?}, expected one of {}",
+                    column.field,
+                    Ntry::FIELDS.join(", ")
+                ));
+            }
+        }
+        // amounts like 100,00 must be quoted to be told from two columns
+        if layout.decimal_separator == layout.delimiter
+            && !matches!(layout.quoting, Quoting::Always | Quoting::NonNumeric)
+        {
+            return Err(format!(
+                "decimal separator {:?} is also the delimiter, quoting must be always or non_numeric",
+                layout.decimal_separator
+            ));
+        }
+        // debit and credit are empty when amount or direction are written
+        let uses = |field: &str| layout.columns.iter().any(|column| column.field == field);
+        if (uses("debit") || uses("credit")) && (uses("amount") || uses("direction")) {
+            return Err(
+                "debit and credit can't be mixed with amount and direction columns".to_string(),
+            );
+        }
+        Ok(layout)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Layout, String> {
+        std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| Layout::from_toml(&text))
+            .map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    /// Turn on the export options giving the columns of the layout, the
+    /// amount columns named win over the ones of the command line.
+    pub fn enable_options(&self, options: &mut ExportOptions) {
+        let uses = |fields: &[&str]| {
+            self.columns
+                .iter()
+                .any(|column| fields.contains(&column.field.as_str()))
+        };
+        if uses(&["debit", "credit"]) {
+            options.amount_columns = AmountColumns::Split;
+        } else if uses(&["direction"]) {
+            options.amount_columns = AmountColumns::Direction;
+        } else if uses(&["amount"]) && options.amount_columns == AmountColumns::Split {
+            options.amount_columns = AmountColumns::Signed;
+        }
+        options.references |= uses(&REFERENCE_FIELDS);
+        options.transaction_codes |= uses(&TRANSACTION_CODE_FIELDS);
+        options.exchange |= uses(&EXCHANGE_FIELDS);
+        options.parties |= uses(&PARTY_FIELDS);
+    }
+}
+
+/// Write entries as CSV in the given layout. Without columns, those of the
+/// first entry are written, as [`crate::write_csv`] does.
+pub fn write_csv_layout<W: Write>(
+    writer: W,
+    ntry_vec: &[Ntry],
+    layout: &Layout,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let columns: Vec<Column> = match (layout.columns.is_empty(), ntry_vec.first()) {
+        (false, _) => layout.columns.clone(),
+        (true, Some(first)) => Ntry::FIELDS
+            .iter()
+            .filter(|field| first.field(field).is_some())
+            .map(|field| Column {
+                field: field.to_string(),
+                header: None,
+            })
+            .collect(),
+        (true, None) => Vec::new(),
+    };
+    if columns.is_empty() {
+        return Ok(());
+    }
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(layout.delimiter as u8)
+        .quote_style(layout.quoting.quote_style())
+        .from_writer(writer);
+    if layout.header {
+        writer.write_record(columns.iter().map(Column::header))?;
+    }
+    for record in ntry_vec {
+        writer.write_record(columns.iter().map(|column| {
+            let value = record.field(&column.field).unwrap_or_default();
+            if layout.decimal_separator != '.' && DECIMAL_FIELDS.contains(&column.field.as_str()) {
+                value.replace('.', &layout.decimal_separator.to_string())
+            } else {
+                value.to_string()
+            }
+        }))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::entry_rows;
+    use crate::fixture::{camt053, entry, statement};
+
+    fn write(layout: &Layout, options: &ExportOptions) -> String {
+        let statements = camt053(&[statement(
+            "S1",
+            1,
+            1,
+            31,
+            &[
+                entry("100.00", "CRDT", "BOOK", "A1", ""),
+                entry("69.50", "DBIT", "BOOK", "A2", ""),
+            ]
+            .concat(),
+        )]);
+        let rows: Vec<Ntry> = statements[0]
+            .entries
+            .iter()
+            .flat_map(|entry| entry_rows("CH93", entry, options))
+            .collect();
+        let mut output = Vec::new();
+        write_csv_layout(&mut output, &rows, layout).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn columns_headers_and_separators() {
+        let layout = Layout::from_toml(
+            r#"
+            delimiter = ","
+            decimal_separator = ","
+            quoting = "non_numeric"
+
+            [[columns]]
+            field = "credit"
+            header = "In"
+
+            [[columns]]
+            field = "debit"
+
+            [[columns]]
+            field = "entry_account_servicer_reference"
+            header = "Ref"
+            "#,
+        )
+        .unwrap();
+        let mut options = ExportOptions::default();
+        layout.enable_options(&mut options);
+        assert!(options.references);
+        assert_eq!(
+            write(&layout, &options),
+            "\"In\",\"debit\",\"Ref\"\n\"100,00\",0,\"A1\"\n0,\"69,50\",\"A2\"\n"
+        );
+    }
+
+    #[test]
+    fn default_layout_writes_the_exported_columns() {
+        let layout = Layout {
+            header: false,
+            ..Layout::default()
+        };
+        assert_eq!(
+            write(&layout, &ExportOptions::default()),
+            "CH93;2023-05-02;;0;100.00;CRDT;CHF;BOOK\nCH93;2023-05-02;;69.50;0;DBIT;CHF;BOOK\n"
+        );
+    }
+
+    #[test]
+    fn signed_amount_column() {
+        let layout =
+            Layout::from_toml("[[columns]]\nfield = \"amount\"\n[[columns]]\nfield = \"date\"\n")
+                .unwrap();
+        let mut options = ExportOptions::default();
+        layout.enable_options(&mut options);
+        assert_eq!(
+            write(&layout, &options),
+            "amount;date\n100.00;2023-05-02\n-69.50;2023-05-02\n"
+        );
+    }
+
+    #[test]
+    fn debit_and_credit_columns_split_amounts() {
+        let layout =
+            Layout::from_toml("[[columns]]\nfield = \"debit\"\n[[columns]]\nfield = \"credit\"\n")
+                .unwrap();
+        let mut options = ExportOptions {
+            amount_columns: AmountColumns::Signed,
+            ..ExportOptions::default()
+        };
+        layout.enable_options(&mut options);
+        assert_eq!(options.amount_columns, AmountColumns::Split);
+        assert_eq!(
+            write(&layout, &options),
+            "debit;credit\n0;100.00\n69.50;0\n"
+        );
+    }
+
+    #[test]
+    fn rejected_layouts() {
+        let error = |text: &str| Layout::from_toml(text).unwrap_err();
+        assert!(error("[[columns]]\nfield = \"amuont\"\n").starts_with("unknown field \"amuont\""));
+        assert!(error("delimiter = \"é\"\n").contains("not an ASCII character"));
+        assert!(
+            error("delimiter = \",\"\ndecimal_separator = \",\"\nquoting = \"never\"\n")
+                .contains("also the delimiter")
+        );
+        assert!(
+            error("[[columns]]\nfield = \"debit\"\n[[columns]]\nfield = \"amount\"\n")
+                .contains("can't be mixed")
+        );
+        assert!(error("quoting = \"nevr\"\n").contains("line 1, column 11"));
+    }
+}
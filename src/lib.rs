@@ -0,0 +1,145 @@
+//! Parser for ISO 20022 CAMT53 bank statements.
+//!
+//! The parser reads a `BkToCstmrStmt` message (camt.053), a
+//! `BkToCstmrAcctRpt` (camt.052) or a `BkToCstmrDbtCdtNtfctn` (camt.054)
+//! one into a typed [`Document`].
+//! For CSV output, [`statement_rows`] flattens its entries into [`Ntry`]
+//! records, one per `TxDtls` when transaction details are present.
+//!
+//! ```no_run
+//! let xml = std::fs::read_to_string("statement.xml").unwrap();
+//! let document = camt_parser::parse_str(&xml).unwrap();
+//! for statement in &document.statements {
+//!     println!("{}: {} entries", statement.id, statement.entries.len());
+//! }
+//! ```
+
+use minidom::Element;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+mod checks;
+mod dedup;
+mod error;
+mod export;
+#[cfg(test)]
+mod fixture;
+mod layout;
+mod link;
+pub mod model;
+mod money;
+mod parser;
+mod reference;
+mod sequence;
+mod transaction_code;
+mod version;
+
+pub use checks::{
+    check_identifiers, check_references, check_summary, reconcile, InvalidIdentifier,
+    InvalidReference, Reconciliation, SummaryMismatch,
+};
+pub use dedup::{find_duplicates, DuplicateEntry, DuplicateKey};
+pub use error::{CamtError, Diagnostic, Diagnostics, Location};
+pub use export::{
+    check_date_format, entry_rows, statement_rows, write_csv, AmountColumns, DateColumn,
+    ExportOptions, IbanFormat, Ntry,
+};
+pub use layout::{write_csv_layout, Column, Layout, Quoting};
+pub use link::{
+    expand_notifications, is_linked, link_notifications, ExpansionMismatch, NotificationLink,
+};
+pub use model::{merge_documents, Document, MessageKind, Statement};
+pub use money::{Currency, Money};
+pub use parser::{ntry_parser, process_camt53, txdtls_parser};
+pub use reference::{
+    format_iban, is_valid_bic, is_valid_creditor_reference, is_valid_iban, is_valid_isr_reference,
+    is_valid_qr_reference,
+};
+pub use sequence::{check_sequence, SequenceIssue};
+pub use version::SchemaVersion;
+
+/// Settings for parsing, the free functions use the defaults.
+///
+/// ```no_run
+/// let options = camt_parser::ParseOptions { lenient: true };
+/// let document = options.parse_file("statement.xml").unwrap();
+/// for skipped in &document.diagnostics {
+///     eprintln!("skipped: {}", skipped.error);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// skip malformed `Ntry`/`TxDtls` elements, recording them in
+    /// [`Document::diagnostics`], instead of failing the whole statement
+    pub lenient: bool,
+}
+
+impl ParseOptions {
+    /// Parse a CAMT53 document held in a string.
+    pub fn parse_str(&self, xml_content: &str) -> Result<Document, CamtError> {
+        let root_element: Element = xml_content.parse().map_err(|source| CamtError::Xml {
+            location: Location::default(),
+            source,
+        })?;
+        process_camt53(&root_element, self)
+    }
+
+    /// Parse a CAMT53 document held in a byte buffer, which must be UTF-8.
+    pub fn parse_bytes(&self, xml_content: &[u8]) -> Result<Document, CamtError> {
+        let xml_content = std::str::from_utf8(xml_content).map_err(|e| CamtError::Io {
+            location: Location::default(),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        })?;
+        self.parse_str(xml_content)
+    }
+
+    /// Parse a CAMT53 document from any reader, e.g. an open file.
+    pub fn parse_reader<R: Read>(&self, mut reader: R) -> Result<Document, CamtError> {
+        let mut xml_content = String::new();
+        reader
+            .read_to_string(&mut xml_content)
+            .map_err(|source| CamtError::Io {
+                location: Location::default(),
+                source,
+            })?;
+        self.parse_str(&xml_content)
+    }
+
+    /// Parse a CAMT53 file, errors carry the file name.
+    pub fn parse_file<P: AsRef<Path>>(&self, path: P) -> Result<Document, CamtError> {
+        let path = path.as_ref();
+        let file_name = path.display().to_string();
+        let mut document = File::open(path)
+            .map_err(|source| CamtError::Io {
+                location: Location::default(),
+                source,
+            })
+            .and_then(|file| self.parse_reader(file))
+            .map_err(|e| e.with_file(&file_name))?;
+        for diagnostic in document.diagnostics.iter_mut() {
+            diagnostic.error.location_mut().file = Some(file_name.clone());
+        }
+        Ok(document)
+    }
+}
+
+/// Parse a CAMT53 document held in a string.
+pub fn parse_str(xml_content: &str) -> Result<Document, CamtError> {
+    ParseOptions::default().parse_str(xml_content)
+}
+
+/// Parse a CAMT53 document held in a byte buffer, which must be UTF-8.
+pub fn parse_bytes(xml_content: &[u8]) -> Result<Document, CamtError> {
+    ParseOptions::default().parse_bytes(xml_content)
+}
+
+/// Parse a CAMT53 document from any reader, e.g. an open file.
+pub fn parse_reader<R: Read>(reader: R) -> Result<Document, CamtError> {
+    ParseOptions::default().parse_reader(reader)
+}
+
+/// Parse a CAMT53 file, errors carry the file name.
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Document, CamtError> {
+    ParseOptions::default().parse_file(path)
+}
@@ -0,0 +1,254 @@
+//! Links between aggregated camt.053 entries and the camt.054
+//! notifications that break them down.
+
+use crate::model::{Entry, MessageKind, Statement};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// A camt.053 (or camt.052) entry and the camt.054 entry detailing it,
+/// as indices into the statements given to [`link_notifications`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotificationLink {
+    pub statement: usize,
+    pub entry: usize,
+    pub notification: usize,
+    pub notification_entry: usize,
+}
+
+/// `AcctSvcrRef` and `NtryRef` of an entry, banks put the shared reference
+/// in either.
+fn link_keys(entry: &Entry) -> impl Iterator<Item = &str> {
+    [&entry.account_servicer_reference, &entry.reference]
+        .into_iter()
+        .flatten()
+        .map(|reference| reference.as_str())
+        .filter(|reference| !reference.is_empty())
+}
+
+/// Do both entries share a reference?
+pub fn is_linked(entry: &Entry, notification_entry: &Entry) -> bool {
+    link_keys(entry).any(|key| link_keys(notification_entry).any(|other| other == key))
+}
+
+/// Find, for every camt.054 entry, the entry of the same account it breaks
+/// down in the other statements.
+pub fn link_notifications(statements: &[Statement]) -> Vec<NotificationLink> {
+    let mut links = Vec::new();
+    for (notification, ntfctn) in statements.iter().enumerate() {
+        if ntfctn.kind != MessageKind::Notification {
+            continue;
+        }
+        for (notification_entry, ntfctn_entry) in ntfctn.entries.iter().enumerate() {
+            let found = statements.iter().enumerate().find_map(|(statement, stmt)| {
+                if stmt.kind == MessageKind::Notification
+                    || stmt.account.identifier() != ntfctn.account.identifier()
+                {
+                    return None;
+                }
+                stmt.entries
+                    .iter()
+                    .position(|entry| is_linked(entry, ntfctn_entry))
+                    .map(|entry| (statement, entry))
+            });
+            if let Some((statement, entry)) = found {
+                links.push(NotificationLink {
+                    statement,
+                    entry,
+                    notification,
+                    notification_entry,
+                });
+            }
+        }
+    }
+    links
+}
+
+/// An aggregated entry whose moved camt.054 transactions do not add up
+/// to its amount.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpansionMismatch {
+    pub statement: String,
+    /// 1-based index of the entry in the statement
+    pub entry: usize,
+    pub amount: Decimal,
+    pub transactions: Decimal,
+}
+
+impl fmt::Display for ExpansionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "statement {}, entry {}: amount {} but its notification transactions add up to {}",
+            self.statement, self.entry, self.amount, self.transactions
+        )
+    }
+}
+
+/// Move the transaction details of linked camt.054 entries into the entries
+/// they break down, when those carry no details themselves. Moved
+/// notification entries are removed, so nothing is exported twice, and
+/// notifications left empty are dropped. Expanded entries whose
+/// transactions do not add up to their amount are returned.
+pub fn expand_notifications(statements: &mut Vec<Statement>) -> Vec<ExpansionMismatch> {
+    let links = link_notifications(statements);
+    let mut targets: Vec<(usize, usize)> = Vec::new();
+    for link in &links {
+        if !targets.contains(&(link.statement, link.entry)) {
+            targets.push((link.statement, link.entry));
+        }
+    }
+
+    let mut used = Vec::new();
+    let mut mismatches = Vec::new();
+    for (statement, entry) in targets {
+        let has_details = statements[statement].entries[entry]
+            .details
+            .iter()
+            .any(|details| !details.transactions.is_empty());
+        if has_details {
+            continue;
+        }
+        let mut details = Vec::new();
+        for link in links
+            .iter()
+            .filter(|link| (link.statement, link.entry) == (statement, entry))
+        {
+            details.extend(
+                statements[link.notification].entries[link.notification_entry]
+                    .details
+                    .iter()
+                    .cloned(),
+            );
+            used.push((link.notification, link.notification_entry));
+        }
+
+        let aggregate = &mut statements[statement].entries[entry];
+        aggregate.details = details;
+        let amount = aggregate.amount.signed(aggregate.credit_debit);
+        let transactions: Decimal = aggregate
+            .details
+            .iter()
+            .flat_map(|details| details.transactions.iter())
+            .map(|transaction| transaction.amount.signed(transaction.credit_debit))
+            .sum();
+        if transactions != amount {
+            mismatches.push(ExpansionMismatch {
+                statement: statements[statement].id.clone(),
+                entry: entry + 1,
+                amount,
+                transactions,
+            });
+        }
+    }
+
+    for (index, statement) in statements.iter_mut().enumerate() {
+        let entries = std::mem::take(&mut statement.entries);
+        statement.entries = entries
+            .into_iter()
+            .enumerate()
+            .filter(|(entry, _)| !used.contains(&(index, *entry)))
+            .map(|(_, entry)| entry)
+            .collect();
+    }
+    statements.retain(|statement| {
+        statement.kind != MessageKind::Notification || !statement.entries.is_empty()
+    });
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{camt053, camt054, details, entry, statement, transaction};
+
+    /// A statement with an aggregated credit of 300.00 and a notification
+    /// entry per amount of `notified`, all with the `AcctSvcrRef` `AGG`.
+    fn statements(notified: &[&str]) -> Vec<Statement> {
+        let aggregate = statement("S1", 1, 1, 31, &entry("300.00", "CRDT", "BOOK", "AGG", ""));
+        let notifications: String = notified
+            .iter()
+            .map(|amount| {
+                entry(
+                    amount,
+                    "CRDT",
+                    "BOOK",
+                    "AGG",
+                    &details(&[transaction(amount, "CRDT", "")]),
+                )
+            })
+            .collect();
+        let mut statements = camt053(&[aggregate]);
+        statements.extend(camt054(&[statement("N1", 1, 2, 2, &notifications)]));
+        statements
+    }
+
+    #[test]
+    fn notifications_link_by_reference() {
+        let statements = statements(&["100.00", "200.00"]);
+        let links = link_notifications(&statements);
+        assert_eq!(
+            links,
+            [
+                NotificationLink {
+                    statement: 0,
+                    entry: 0,
+                    notification: 1,
+                    notification_entry: 0,
+                },
+                NotificationLink {
+                    statement: 0,
+                    entry: 0,
+                    notification: 1,
+                    notification_entry: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_account_is_not_linked() {
+        let mut statements = statements(&["300.00"]);
+        statements[1].account.iban = Some("CH5604835012345678009".to_string());
+        assert!(link_notifications(&statements).is_empty());
+    }
+
+    #[test]
+    fn transactions_move_into_the_aggregate() {
+        let mut statements = statements(&["100.00", "200.00"]);
+        assert!(expand_notifications(&mut statements).is_empty());
+        // the emptied notification is dropped
+        assert_eq!(statements.len(), 1);
+        let amounts: Vec<Decimal> = statements[0].entries[0]
+            .details
+            .iter()
+            .flat_map(|details| details.transactions.iter())
+            .map(|transaction| transaction.amount.amount)
+            .collect();
+        assert_eq!(amounts, [Decimal::new(10000, 2), Decimal::new(20000, 2)]);
+    }
+
+    #[test]
+    fn transactions_not_adding_up() {
+        let mut statements = statements(&["100.00"]);
+        let mismatches = expand_notifications(&mut statements);
+        assert_eq!(
+            mismatches,
+            [ExpansionMismatch {
+                statement: "S1".to_string(),
+                entry: 1,
+                amount: Decimal::new(30000, 2),
+                transactions: Decimal::new(10000, 2),
+            }]
+        );
+        assert_eq!(statements.len(), 1);
+    }
+
+    #[test]
+    fn aggregate_with_details_is_left_alone() {
+        let mut statements = statements(&["300.00"]);
+        statements[0].entries[0].details = statements[1].entries[0].details.clone();
+        assert!(expand_notifications(&mut statements).is_empty());
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[1].entries.len(), 1);
+    }
+}
@@ -1,44 +1,42 @@
-use csv::WriterBuilder;
+use camt_parser::{
+    check_date_format, check_identifiers, check_references, check_sequence, check_summary,
+    entry_rows, expand_notifications, find_duplicates, merge_documents, reconcile,
+    write_csv_layout, AmountColumns, DateColumn, Diagnostic, Document, ExportOptions, IbanFormat,
+    Layout, Ntry, ParseOptions,
+};
 use glob::glob;
-use minidom::Element;
-//use minidom::Error as MiniDomError;
-use minidom::NSChoice::Any as NSAny;
 use std::fs::File;
-use std::io::{BufWriter, Read};
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
 // cli
-use clap::{Arg, Command};
-
-// Statement
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
-struct Stmt {
-    iban: String,
-    entries_count: i64,
-}
-
-// Entry (NTry)
-#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
-struct Ntry {
-    account: String,     // Account
-    date: String,        // date
-    description: String, //description of transaction
-    debit: String,       // debit amount
-    credit: String,      // credit amount
-    ntry_type: String,   // type of entry
-}
+use clap::{Arg, ArgAction, Command};
 
 fn write_csv_result(
     filename: &str,
-    ntry_vec: &Vec<Ntry>,
+    ntry_vec: &[Ntry],
+    layout: &Layout,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // open output csv file
     let file = File::create(filename)?;
-    let mut writer = WriterBuilder::new()
-        .delimiter(b';')
-        .from_writer(BufWriter::new(file)); // and write data in
-    for record in ntry_vec {
-        writer.serialize(record)?;
+    write_csv_layout(BufWriter::new(file), ntry_vec, layout)
+}
+
+// write skipped fragments for manual review, wrapped so the file stays valid XML
+fn write_quarantine(
+    filename: &str,
+    diagnostics: &[Diagnostic],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(writer, "<Quarantine>")?;
+    for diagnostic in diagnostics {
+        // "--" is not allowed inside an XML comment
+        let reason = diagnostic.error.to_string().replace("--", "- -");
+        writeln!(writer, "<!-- {} -->", reason)?;
+        writeln!(writer, "{}", diagnostic.fragment)?;
     }
+    writeln!(writer, "</Quarantine>")?;
     writer.flush()?;
     Ok(())
 }
@@ -64,6 +62,129 @@ fn main() {
                 .help("file to be parsed CAMT53 format")
                 .default_value("*.xml"),
         )
+        .arg(
+            Arg::new("date_format")
+                .long("date-format")
+                .value_name("FORMAT")
+                .value_parser(|format: &str| check_date_format(format).map(|_| format.to_string()))
+                .help("strftime format of the date column, e.g. '%d.%m.%Y'")
+                .default_value("%Y-%m-%d"),
+        )
+        .arg(
+            Arg::new("date_column")
+                .long("date-column")
+                .value_name("DATE")
+                .value_parser(["booking", "value"])
+                .help("Date of the date column, the booking date (BookgDt) or the value date (ValDt, the booking date when missing)")
+                .default_value("booking"),
+        )
+        .arg(
+            Arg::new("iban_format")
+                .long("iban-format")
+                .value_name("FORMAT")
+                .value_parser(["electronic", "print"])
+                .help("Write IBANs without spaces (electronic) or in groups of four (print), e.g. 'CH93 0076 2011 6238 5295 7'")
+                .default_value("electronic"),
+        )
+        .arg(
+            Arg::new("layout")
+                .long("layout")
+                .value_name("FILE")
+                .value_parser(|path: &str| Layout::from_file(Path::new(path)))
+                .help("TOML file giving the columns, their headers, the delimiter, quoting and decimal separator"),
+        )
+        .arg(
+            Arg::new("amount_columns")
+                .long("amount-columns")
+                .value_name("MODE")
+                .value_parser(["split", "signed", "direction"])
+                .help("Write amounts in debit and credit columns (split), in one amount column negative for debits (signed), or without sign with a credit/debit direction column (direction)")
+                .default_value("split"),
+        )
+        .arg(
+            Arg::new("empty_zero")
+                .long("empty-zero")
+                .action(ArgAction::SetTrue)
+                .help("Leave the zero side of split debit and credit columns empty instead of 0"),
+        )
+        .arg(
+            Arg::new("status")
+                .long("status")
+                .value_name("STATUS")
+                .value_delimiter(',')
+                .value_parser(["BOOK", "PDNG", "INFO"])
+                .ignore_case(true)
+                .help("Only export entries with these statuses, e.g. 'BOOK,PDNG', entries without Sts count as BOOK"),
+        )
+        .arg(
+            Arg::new("references")
+                .long("references")
+                .action(ArgAction::SetTrue)
+                .help("Add columns with the entry references (NtryRef, AcctSvcrRef) and the transaction references (MsgId, AcctSvcrRef, PmtInfId, InstrId, EndToEndId, TxId, MndtId, ChqNb, ClrSysRef) and the creditor reference (RmtInf/Strd/CdtrRefInf)"),
+        )
+        .arg(
+            Arg::new("transaction_codes")
+                .long("transaction-codes")
+                .action(ArgAction::SetTrue)
+                .help("Add columns with the bank transaction code (BkTxCd), e.g. PMNT/RCDT/ESCT, and its label, e.g. 'SEPA credit transfer received'"),
+        )
+        .arg(
+            Arg::new("charge_lines")
+                .long("charge-lines")
+                .action(ArgAction::SetTrue)
+                .help("Put charges included in an amount (Chrgs with ChrgInclInd) on lines of their own, taken out of the line they are included in"),
+        )
+        .arg(
+            Arg::new("exchange")
+                .long("exchange")
+                .action(ArgAction::SetTrue)
+                .help("Add columns with the amount in foreign currency and the exchange rate (AmtDtls with CcyXchg)"),
+        )
+        .arg(
+            Arg::new("parties")
+                .long("parties")
+                .action(ArgAction::SetTrue)
+                .help("Add columns with the name, address and identification of the debtor and creditor, the names of the ultimate debtor and creditor, the BIC of their agents, and their accounts, IBAN or other identifier, and the one of the counterparty"),
+        )
+        .arg(
+            Arg::new("pending_output")
+                .long("pending-output")
+                .value_name("FILE")
+                .help("Write pending (PDNG) entries to this file instead of the output file"),
+        )
+        .arg(
+            Arg::new("expand_notifications")
+                .long("expand-notifications")
+                .action(ArgAction::SetTrue)
+                .help("Replace aggregated entries by the camt.054 transactions detailing them, matched on AcctSvcrRef/NtryRef"),
+        )
+        .arg(
+            Arg::new("duplicates")
+                .long("duplicates")
+                .value_name("MODE")
+                .value_parser(["keep", "drop", "flag"])
+                .help("What to do with entries already given by another statement, matched on AcctSvcrRef, NtryRef, EndToEndId or content: keep them, drop them, or flag them in a duplicate column")
+                .default_value("keep"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Treat balance, transaction summary, statement sequence and expanded notification mismatches and invalid references, IBANs and BICs as errors, no output is written then"),
+        )
+        .arg(
+            Arg::new("lenient")
+                .long("lenient")
+                .action(ArgAction::SetTrue)
+                .help("Skip malformed entries and files instead of aborting, and report them at the end"),
+        )
+        .arg(
+            Arg::new("quarantine")
+                .long("quarantine")
+                .value_name("FILE")
+                .requires("lenient")
+                .help("Write the XML of skipped entries to this file"),
+        )
         /*        .after_help(
                     "Longer explanation to appear after the options when \
                          displaying the help information from --help or -h",
@@ -81,225 +202,229 @@ fn main() {
         .map(|v| v.as_str())
         .collect::<Vec<_>>();
 
-    let mut entries = Vec::<Ntry>::new();
+    let options = ParseOptions {
+        lenient: matches.get_flag("lenient"),
+    };
+
+    let layout = matches
+        .get_one::<Layout>("layout")
+        .cloned()
+        .unwrap_or_default();
+
+    let mut export_options = ExportOptions {
+        date_format: matches
+            .get_one::<String>("date_format")
+            .expect("has a default")
+            .clone(),
+        date_column: match matches
+            .get_one::<String>("date_column")
+            .expect("has a default")
+            .as_str()
+        {
+            "value" => DateColumn::Value,
+            _ => DateColumn::Booking,
+        },
+        iban_format: match matches
+            .get_one::<String>("iban_format")
+            .expect("has a default")
+            .as_str()
+        {
+            "print" => IbanFormat::Print,
+            _ => IbanFormat::Electronic,
+        },
+        amount_columns: match matches
+            .get_one::<String>("amount_columns")
+            .expect("has a default")
+            .as_str()
+        {
+            "signed" => AmountColumns::Signed,
+            "direction" => AmountColumns::Direction,
+            _ => AmountColumns::Split,
+        },
+        empty_zero: matches.get_flag("empty_zero"),
+        references: matches.get_flag("references"),
+        transaction_codes: matches.get_flag("transaction_codes"),
+        charge_lines: matches.get_flag("charge_lines"),
+        exchange: matches.get_flag("exchange"),
+        parties: matches.get_flag("parties"),
+    };
+    layout.enable_options(&mut export_options);
+
+    let mut documents = Vec::<Document>::new();
+    let mut skipped = Vec::<Diagnostic>::new();
 
     for filenames in input_filenames {
         for filename in glob(filenames).expect("invalid glob pattern") {
             // Open the CAMT.053 file
             let filename = filename.unwrap();
-            let mut file = File::open(filename.clone()).expect("Failed to open file");
 
             println!("processing file: {:?}", filename);
 
-            // read the file into memory
-            let mut xml_content = String::new();
-            file.read_to_string(&mut xml_content)
-                .expect("Failed to read CAMT53 file");
-
-            // parse XML file
-            let xml_content = xml_content.as_str();
-            let root_element = xml_content.parse().expect("Failed to parse XML");
-
             // Extract and process the desired information from the CAMT53 file
-            let result = process_camt53(&root_element);
-            entries.extend(result);
+            match options.parse_file(&filename) {
+                Ok(mut document) => {
+                    match &document.version {
+                        Some(version) => println!("  {} message", version),
+                        None => println!(
+                            "  {} message, unknown namespace {:?}",
+                            document.kind.name(),
+                            document.namespace
+                        ),
+                    }
+                    skipped.append(&mut document.diagnostics);
+                    documents.push(document);
+                }
+                Err(e) if options.lenient => {
+                    // nothing to quarantine, the file itself is broken
+                    skipped.push(Diagnostic {
+                        error: e,
+                        fragment: String::new(),
+                    });
+                }
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    std::process::exit(1);
+                }
+            }
         }
     }
 
-    write_csv_result(output_filename, &entries).expect("CSV output failed");
-}
-
-fn process_camt53(root_element: &Element) -> Vec<Ntry> {
-    // Parse the XML content
-    let customer_statment = root_element.get_child("BkToCstmrStmt", NSAny).unwrap();
-
-    let stmt = customer_statment.get_child("Stmt", NSAny).unwrap();
-
-    // Create a vector to hold the parsed entries
-    let mut ntry_vec: Vec<Ntry> = Vec::new();
-    // create other data to collect
-    let mut stmt_info = Stmt {
-        iban: String::from("IBAN"),
-        entries_count: 0,
-    };
-
-    // iterate over statement children and process according to type
-    for child in stmt.children() {
-        // data about statment
-        if child.is("ElctrncSeqNb", NSAny) {
-            stmt_info.entries_count = child.text().parse::<i64>().unwrap();
-        }
-
-        // data about account
-        if child.is("Acct", NSAny) {
-            stmt_info.iban = child
-                .get_child("Id", NSAny)
-                .and_then(|container| container.get_child("IBAN", NSAny))
-                .expect("no IBAN")
-                .text();
-        }
-        // entries
-        if child.is("Ntry", NSAny) {
-            let res = ntry_parser(stmt_info.iban.clone(), &child);
-            ntry_vec.extend(res);
-            // DEBUG // println!("one record");
+    // statements split over several files are joined before export
+    let mut entries = Vec::<Ntry>::new();
+    // checks are warnings, unless --strict
+    let strict = matches.get_flag("strict");
+    let severity = if strict { "error:" } else { "warning:" };
+    let mut failed_checks = 0;
+
+    let mut statements = merge_documents(documents);
+    if matches.get_flag("expand_notifications") {
+        for mismatch in expand_notifications(&mut statements) {
+            println!("{} {}", severity, mismatch);
+            failed_checks += 1;
         }
     }
-    return ntry_vec;
-}
-
-fn ntry_parser(account: String, child: &Element) -> Vec<Ntry> {
-    let mut result: Vec<Ntry> = Vec::new();
-    // let's push some data
-
-    // get amount of entry
-    let amount = child
-        .get_child("Amt", NSAny)
-        .expect("No Amts in Ntry")
-        .text();
-
-    // get booking date, which will be used a reference date
-    let date = child
-        .get_child("BookgDt", NSAny)
-        .and_then(|container| container.get_child("Dt", NSAny))
-        .expect("no Dt in Bookgdt")
-        .text();
-
-    // get NTry description
-    let descr = child
-        .get_child("AddtlNtryInf", NSAny)
-        .expect("cannot get AddtlNtryInf")
-        .text();
-
-    // get type of booking
-    let ntry_type = child
-        .get_child("CdtDbtInd", NSAny)
-        .expect("error in CdtDbtInd")
-        .text();
-
-    // create statement record
-    let mut record = Ntry {
-        account: account,
-        date: date,
-        description: descr,
-        debit: "0".to_string(),
-        credit: "0".to_string(),
-        ntry_type: ntry_type,
+    // statements of an account, across all files
+    for issue in check_sequence(&statements) {
+        println!("{} {}", severity, issue);
+        failed_checks += 1;
+    }
+    // only entries repeated across statements, see find_duplicates
+    let duplicates_mode = matches
+        .get_one::<String>("duplicates")
+        .expect("has a default")
+        .as_str();
+    let duplicates = match duplicates_mode {
+        "keep" => Vec::new(),
+        _ => find_duplicates(&statements),
     };
 
-    // get type of booking
-    let ntry_type = child
-        .get_child("CdtDbtInd", NSAny)
-        .expect("error in CdtDbtInd")
-        .text();
-
-    // push amount in correct field
-    // println!("tx type {}", ntry_type);
-    if ntry_type.eq("CRDT") {
-        record.credit = amount;
-    } else {
-        record.debit = amount;
-    }
+    let statuses = matches
+        .get_many::<String>("status")
+        .unwrap_or_default()
+        .map(|status| status.to_uppercase())
+        .collect::<Vec<_>>();
 
-    let mut had_ntry_dtls = false;
-    for entry in child.children() {
-        if entry.is("NtryDtls", NSAny) {
-            // DEBUG // println!("found NtryDtls");
-            for ntry_dtls_child in entry.children() {
-                if ntry_dtls_child.is("TxDtls", NSAny) {
-                    // DEBUG // println!("found txdtls");
-                    let txdtls = txdtls_parser(&record, ntry_dtls_child);
-                    result.push(txdtls);
-                    had_ntry_dtls = true;
-                }
+    for (index, statement) in statements.iter().enumerate() {
+        println!(
+            "statement {} {} seq {} {}: {} entries",
+            statement.id,
+            statement
+                .account
+                .identifier()
+                .unwrap_or_else(|| "-".to_string()),
+            statement
+                .electronic_sequence_number
+                .map(|seq| seq.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            statement
+                .from_to
+                .as_ref()
+                .map(|period| format!("{} - {}", period.from.date(), period.to.date()))
+                .unwrap_or_default(),
+            statement.entries.len()
+        );
+        match reconcile(statement) {
+            Some(check) if check.is_balanced() => println!("  balance ok: {}", check),
+            Some(check) => {
+                println!("  {} balance mismatch: {}", severity, check);
+                failed_checks += 1;
             }
+            None => println!("  balance not checked, no opening or closing balance"),
         }
-    }
-
-    if had_ntry_dtls == false {
-        result.push(record)
-    }
-    return result;
-}
-
-fn txdtls_parser(entry: &Ntry, tx_dtls: &Element) -> Ntry {
-    // DEBUG // println!("found a txdtls");
-    let mut result = entry.clone();
-    let mut operation = Err(());
-    let mut amount = Err(());
-
-    for child in tx_dtls.children() {
-        // amount of transaction
-        if child.is("Amt", NSAny) {
-            amount = Ok(child.text());
+        for mismatch in check_summary(statement) {
+            println!("  {} {}", severity, mismatch);
+            failed_checks += 1;
         }
-
-        // type of transaction
-        if child.is("CdtDbtInd", NSAny) {
-            operation = Ok(child.text());
+        for invalid in check_references(statement) {
+            println!("  {} {}", severity, invalid);
+            failed_checks += 1;
         }
-
-        // corresponding party
-        if child.is("RltdPties", NSAny) {
-            // find either Cdtr or Dbtr Nm
-            let mut partner_nm = "unknown_partner".to_string();
-            let mut iban = "unknown_iban".to_string();
-            let not_found_element = Element::builder("NotFound", "NotFound")
-                .append("Not Found")
-                .build();
-
-            if let Some(cdtr) = child.get_child("Cdtr", NSAny) {
-                partner_nm = cdtr.get_child("Nm", NSAny).expect("Cdtr without Nm").text();
-                match child.get_child("CdtrAcct", NSAny) {
-                    Some(cdtracct) => {
-                        iban = cdtracct
-                            .get_child("Id", NSAny)
-                            .and_then(|container| container.get_child("IBAN", NSAny))
-                            .expect("no cdtr IBAN in RltdPties")
-                            .text();
-                    }
-                    _ => iban = "no IBAN".to_string(),
+        for invalid in check_identifiers(statement) {
+            println!("  {} {}", severity, invalid);
+            failed_checks += 1;
+        }
+        let account = export_options
+            .format_account(&statement.account)
+            .unwrap_or_default();
+        for (entry_index, entry) in statement.entries.iter().enumerate() {
+            let status = entry.status.as_ref().map_or("BOOK", |status| status.code());
+            if !statuses.is_empty() && !statuses.iter().any(|wanted| wanted == status) {
+                continue;
+            }
+            let duplicate = duplicates
+                .iter()
+                .find(|duplicate| duplicate.statement == index && duplicate.entry == entry_index)
+                .map(|duplicate| {
+                    format!(
+                        "{} in {}",
+                        duplicate.key, statements[duplicate.original_statement].id
+                    )
+                });
+            if let Some(duplicate) = &duplicate {
+                println!("  duplicate entry {}: {}", entry_index + 1, duplicate);
+                if duplicates_mode == "drop" {
+                    continue;
                 }
             }
-
-            if let Some(dbtr) = child.get_child("Dbtr", NSAny) {
-                partner_nm = dbtr.get_child("Nm", NSAny).expect("Cdtr without Nm").text();
-                match child.get_child("DbtrAcct", NSAny) {
-                    Some(dbtracct) => {
-                        iban = dbtracct
-                            .get_child("Id", NSAny)
-                            .and_then(|container| container.get_child("IBAN", NSAny))
-                            .unwrap_or(&not_found_element)
-                            .text()
-                    }
-
-                    _ => iban = "UKNOWN IBAN".to_string(),
+            for mut record in entry_rows(&account, entry, &export_options) {
+                if duplicates_mode == "flag" {
+                    record.duplicate = Some(duplicate.clone().unwrap_or_default());
                 }
+                entries.push(record);
             }
-
-            let mut description = partner_nm;
-            description.push_str(" - ");
-            description.push_str(&iban);
-            result.description = description;
         }
+    }
 
-        // Remote Information / Ustrd
-        if child.is("RmtInf", NSAny) {
-            let ustrd = child
-                .get_child("Ustrd", NSAny)
-                .expect("RmtInf without Ustrd")
-                .text();
-            result.description.push_str(&ustrd);
+    if strict && failed_checks > 0 {
+        eprintln!(
+            "error: {} check(s) failed, no output written",
+            failed_checks
+        );
+        std::process::exit(1);
+    }
+
+    if let Some(pending_filename) = matches.get_one::<String>("pending_output") {
+        let (pending, booked): (Vec<Ntry>, Vec<Ntry>) = entries
+            .into_iter()
+            .partition(|record| record.status == "PDNG");
+        write_csv_result(pending_filename, &pending, &layout).expect("CSV output failed");
+        entries = booked;
+    }
+
+    write_csv_result(output_filename, &entries, &layout).expect("CSV output failed");
+
+    if !skipped.is_empty() {
+        println!("skipped {} element(s):", skipped.len());
+        for diagnostic in &skipped {
+            println!("  {}", diagnostic.error);
         }
     }
-    let amount = amount.expect("did not find amount");
-    if operation.expect("did not found operation type").eq("DBIT") {
-        result.debit = amount;
-        result.credit = "0".to_string();
-    } else {
-        result.credit = amount;
-        result.debit = "0".to_string();
+
+    if let Some(quarantine_filename) = matches.get_one::<String>("quarantine") {
+        let fragments = skipped
+            .into_iter()
+            .filter(|diagnostic| !diagnostic.fragment.is_empty())
+            .collect::<Vec<_>>();
+        write_quarantine(quarantine_filename, &fragments).expect("quarantine output failed");
     }
-    // DEBUG // println!("found {:?}", result);
-    return result;
 }
@@ -0,0 +1,995 @@
+//! Typed representation of a `BkToCstmrStmt` message.
+//!
+//! Element names are spelled out, the ISO tag is given in each doc comment.
+
+use crate::error::Diagnostic;
+use crate::money::{Currency, Money};
+use crate::reference;
+use crate::transaction_code;
+use crate::version::SchemaVersion;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+
+/// `CdtDbtInd`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreditDebit {
+    Credit,
+    Debit,
+}
+
+impl CreditDebit {
+    pub fn from_code(code: &str) -> Option<CreditDebit> {
+        match code {
+            "CRDT" => Some(CreditDebit::Credit),
+            "DBIT" => Some(CreditDebit::Debit),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            CreditDebit::Credit => "CRDT",
+            CreditDebit::Debit => "DBIT",
+        }
+    }
+}
+
+/// `Dt` or `DtTm`, banks send either for booking and value dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateAndDateTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl DateAndDateTime {
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            DateAndDateTime::Date(date) => *date,
+            DateAndDateTime::DateTime(datetime) => datetime.date(),
+        }
+    }
+
+    /// A plain date counts as midnight.
+    pub fn date_time(&self) -> NaiveDateTime {
+        match self {
+            DateAndDateTime::Date(date) => date.and_time(NaiveTime::MIN),
+            DateAndDateTime::DateTime(datetime) => *datetime,
+        }
+    }
+}
+
+/// Which message a document holds, statements and reports share one model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageKind {
+    /// camt.053 `BkToCstmrStmt`, end of day statement
+    #[default]
+    Statement,
+    /// camt.052 `BkToCstmrAcctRpt`, intraday account report
+    Report,
+    /// camt.054 `BkToCstmrDbtCdtNtfctn`, debit/credit notification
+    Notification,
+}
+
+impl MessageKind {
+    /// all known messages
+    pub const ALL: [MessageKind; 3] = [
+        MessageKind::Statement,
+        MessageKind::Report,
+        MessageKind::Notification,
+    ];
+
+    /// element below `Document`
+    pub fn root(&self) -> &'static str {
+        match self {
+            MessageKind::Statement => "BkToCstmrStmt",
+            MessageKind::Report => "BkToCstmrAcctRpt",
+            MessageKind::Notification => "BkToCstmrDbtCdtNtfctn",
+        }
+    }
+
+    /// repeated element holding one account, parsed into a [`Statement`]
+    pub fn item(&self) -> &'static str {
+        match self {
+            MessageKind::Statement => "Stmt",
+            MessageKind::Report => "Rpt",
+            MessageKind::Notification => "Ntfctn",
+        }
+    }
+
+    /// short name, e.g. `camt.053`
+    pub fn name(&self) -> &'static str {
+        match self {
+            MessageKind::Statement => "camt.053",
+            MessageKind::Report => "camt.052",
+            MessageKind::Notification => "camt.054",
+        }
+    }
+}
+
+/// `Document`, one parsed file
+#[derive(Debug, Default)]
+pub struct Document {
+    pub kind: MessageKind,
+    /// namespace of the `Document` element
+    pub namespace: String,
+    /// schema version told by the namespace, if recognised
+    pub version: Option<SchemaVersion>,
+    pub group_header: GroupHeader,
+    pub statements: Vec<Statement>,
+    /// entries skipped in lenient mode
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// `GrpHdr`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupHeader {
+    /// `MsgId`
+    pub message_id: String,
+    /// `CreDtTm`
+    pub creation_date_time: NaiveDateTime,
+    /// `MsgPgntn`, set when a statement is split over several messages
+    pub pagination: Option<Pagination>,
+}
+
+/// `MsgPgntn`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pagination {
+    /// `PgNb`
+    pub page_number: u32,
+    /// `LastPgInd`
+    pub last_page: bool,
+}
+
+/// `FrToDt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateTimePeriod {
+    /// `FrDtTm`
+    pub from: NaiveDateTime,
+    /// `ToDtTm`
+    pub to: NaiveDateTime,
+}
+
+/// `Stmt`, or `Rpt`/`Ntfctn` of a camt.052/camt.054 message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Statement {
+    /// message the statement comes from
+    pub kind: MessageKind,
+    /// `Id`
+    pub id: String,
+    /// `ElctrncSeqNb`
+    pub electronic_sequence_number: Option<u64>,
+    /// `LglSeqNb`
+    pub legal_sequence_number: Option<u64>,
+    /// `CreDtTm`
+    pub creation_date_time: Option<NaiveDateTime>,
+    /// `FrToDt`
+    pub from_to: Option<DateTimePeriod>,
+    /// `Acct`
+    pub account: Account,
+    /// `Bal`
+    pub balances: Vec<Balance>,
+    /// `TxsSummry`
+    pub summary: Option<TransactionsSummary>,
+    /// `Ntry`
+    pub entries: Vec<Entry>,
+}
+
+impl Statement {
+    /// First balance of the given type.
+    pub fn balance(&self, balance_type: &BalanceType) -> Option<&Balance> {
+        self.balances
+            .iter()
+            .find(|balance| &balance.balance_type == balance_type)
+    }
+
+    /// Append the balances and entries of another page of this statement.
+    pub fn merge_page(&mut self, page: Statement) {
+        for balance in page.balances {
+            if !self.balances.contains(&balance) {
+                self.balances.push(balance);
+            }
+        }
+        self.entries.extend(page.entries);
+    }
+}
+
+/// Statements of all documents, with statements split over several
+/// messages (`MsgPgntn`) joined into one. Pages of a statement share its
+/// `Id` and account, a page seen twice is only taken once. Order of first
+/// appearance is kept.
+pub fn merge_documents<I: IntoIterator<Item = Document>>(documents: I) -> Vec<Statement> {
+    let mut merged: Vec<Statement> = Vec::new();
+    // index in `merged` and page numbers of the paginated statements
+    let mut paginated: Vec<(usize, Vec<u32>)> = Vec::new();
+    for document in documents {
+        let page_number = match &document.group_header.pagination {
+            Some(pagination) => pagination.page_number,
+            None => {
+                merged.extend(document.statements);
+                continue;
+            }
+        };
+        for statement in document.statements {
+            let known = paginated.iter_mut().find(|(index, _)| {
+                merged[*index].id == statement.id
+                    && merged[*index].account.identifier() == statement.account.identifier()
+            });
+            match known {
+                Some((_, pages)) if pages.contains(&page_number) => {}
+                Some((index, pages)) => {
+                    pages.push(page_number);
+                    merged[*index].merge_page(statement);
+                }
+                None => {
+                    paginated.push((merged.len(), vec![page_number]));
+                    merged.push(statement);
+                }
+            }
+        }
+    }
+    merged
+}
+
+/// `Acct`, `CdtrAcct` or `DbtrAcct`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Account {
+    /// `Id/IBAN`
+    pub iban: Option<String>,
+    /// `Id/Othr`, a domestic or postal account number, a card number...
+    pub other: Option<OtherAccount>,
+    /// `Prxy`, e.g. a phone number standing for the account
+    pub proxy: Option<AccountProxy>,
+    /// `Nm`
+    pub name: Option<String>,
+    /// `Ccy`
+    pub currency: Option<Currency>,
+}
+
+impl Account {
+    /// The IBAN without spaces, else the other identifier, else the proxy.
+    pub fn identifier(&self) -> Option<String> {
+        if let Some(iban) = &self.iban {
+            return Some(
+                iban.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| c.to_ascii_uppercase())
+                    .collect(),
+            );
+        }
+        self.other
+            .as_ref()
+            .map(|other| other.id.trim().to_string())
+            .or_else(|| self.proxy.as_ref().map(|proxy| proxy.id.trim().to_string()))
+    }
+}
+
+/// `Id/Othr` of an account
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtherAccount {
+    /// `Id`
+    pub id: String,
+    /// `SchmeNm/Cd` or `SchmeNm/Prtry`, e.g. `BBAN` or `CUID`
+    pub scheme: Option<String>,
+    /// `Issr`
+    pub issuer: Option<String>,
+}
+
+/// `Prxy` of an account, since version 8
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountProxy {
+    /// `Tp/Cd` or `Tp/Prtry`, e.g. `TELE` or `EMAL`
+    pub proxy_type: Option<String>,
+    /// `Id`
+    pub id: String,
+}
+
+/// `Bal/Tp/CdOrPrtry`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceType {
+    /// `OPBD`, opening booked
+    Opening,
+    /// `CLBD`, closing booked
+    Closing,
+    /// `CLAV`, closing available
+    ClosingAvailable,
+    /// `PRCD`, previously closed booked, the opening balance of some banks
+    PreviouslyClosed,
+    /// `ITBD`, interim booked, the running balance of intraday reports
+    InterimBooked,
+    /// any other code, or a proprietary one
+    Other(String),
+}
+
+impl BalanceType {
+    pub fn from_code(code: &str) -> BalanceType {
+        match code {
+            "OPBD" => BalanceType::Opening,
+            "CLBD" => BalanceType::Closing,
+            "CLAV" => BalanceType::ClosingAvailable,
+            "PRCD" => BalanceType::PreviouslyClosed,
+            "ITBD" => BalanceType::InterimBooked,
+            _ => BalanceType::Other(code.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            BalanceType::Opening => "OPBD",
+            BalanceType::Closing => "CLBD",
+            BalanceType::ClosingAvailable => "CLAV",
+            BalanceType::PreviouslyClosed => "PRCD",
+            BalanceType::InterimBooked => "ITBD",
+            BalanceType::Other(code) => code,
+        }
+    }
+}
+
+/// `Bal`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Balance {
+    /// `Tp/CdOrPrtry`
+    pub balance_type: BalanceType,
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`
+    pub credit_debit: CreditDebit,
+    /// `Dt`
+    pub date: Option<DateAndDateTime>,
+}
+
+impl Balance {
+    /// The amount, negative for a debit (overdrawn) balance.
+    pub fn signed_amount(&self) -> Decimal {
+        self.amount.signed(self.credit_debit)
+    }
+}
+
+/// `TxsSummry`, the bank's own count of the entries
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionsSummary {
+    /// `TtlNtries`
+    pub total: Option<NumberAndSum>,
+    /// `TtlNtries/TtlNetNtry`, signed
+    pub total_net: Option<Decimal>,
+    /// `TtlCdtNtries`
+    pub total_credit: Option<NumberAndSum>,
+    /// `TtlDbtNtries`
+    pub total_debit: Option<NumberAndSum>,
+}
+
+/// `NbOfNtries` and `Sum` of a `TxsSummry` total
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumberAndSum {
+    /// `NbOfNtries`
+    pub number_of_entries: Option<u64>,
+    /// `Sum`, of the amounts regardless of their direction
+    pub sum: Option<Decimal>,
+}
+
+/// `Sts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// `BOOK`
+    Booked,
+    /// `PDNG`, not yet booked, typical of intraday reports
+    Pending,
+    /// `INFO`
+    Information,
+    Other(String),
+}
+
+impl EntryStatus {
+    pub fn from_code(code: &str) -> EntryStatus {
+        match code {
+            "BOOK" => EntryStatus::Booked,
+            "PDNG" => EntryStatus::Pending,
+            "INFO" => EntryStatus::Information,
+            _ => EntryStatus::Other(code.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            EntryStatus::Booked => "BOOK",
+            EntryStatus::Pending => "PDNG",
+            EntryStatus::Information => "INFO",
+            EntryStatus::Other(code) => code,
+        }
+    }
+}
+
+/// `Ntry`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// `NtryRef`
+    pub reference: Option<String>,
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`
+    pub credit_debit: CreditDebit,
+    /// `AmtDtls`
+    pub amount_details: Option<AmountDetails>,
+    /// `Sts`
+    pub status: Option<EntryStatus>,
+    /// `BookgDt`
+    pub booking_date: DateAndDateTime,
+    /// `ValDt`
+    pub value_date: Option<DateAndDateTime>,
+    /// `Avlbty`, may repeat
+    pub availability: Vec<Availability>,
+    /// `AcctSvcrRef`
+    pub account_servicer_reference: Option<String>,
+    /// `BkTxCd`
+    pub bank_transaction_code: Option<BankTransactionCode>,
+    /// `Chrgs`
+    pub charges: Vec<Charge>,
+    /// `Intrst`
+    pub interest: Vec<Interest>,
+    /// `NtryDtls`
+    pub details: Vec<EntryDetails>,
+    /// `TxDtls` left out in lenient mode because they failed to parse
+    pub skipped_transactions: usize,
+    /// `AddtlNtryInf`
+    pub additional_info: Option<String>,
+}
+
+impl Entry {
+    pub fn is_pending(&self) -> bool {
+        self.status == Some(EntryStatus::Pending)
+    }
+}
+
+/// `Avlbty`, when (part of) the amount of an entry can be used
+#[derive(Debug, Clone, PartialEq)]
+pub struct Availability {
+    /// `Dt`
+    pub date: AvailabilityDate,
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`
+    pub credit_debit: CreditDebit,
+}
+
+/// `Avlbty/Dt`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvailabilityDate {
+    /// `NbOfDays`, float days after the booking
+    Days(u64),
+    /// `ActlDt`
+    Date(NaiveDate),
+}
+
+/// `NtryDtls`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryDetails {
+    /// `TxDtls`
+    pub transactions: Vec<TransactionDetails>,
+}
+
+/// `TxDtls`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionDetails {
+    /// `Refs`
+    pub references: References,
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`
+    pub credit_debit: CreditDebit,
+    /// `AmtDtls`
+    pub amount_details: Option<AmountDetails>,
+    /// `BkTxCd`
+    pub bank_transaction_code: Option<BankTransactionCode>,
+    /// `Chrgs`
+    pub charges: Vec<Charge>,
+    /// `Intrst`
+    pub interest: Vec<Interest>,
+    /// `RltdPties`
+    pub related_parties: Option<RelatedParties>,
+    /// `RltdAgts`
+    pub related_agents: Option<RelatedAgents>,
+    /// `RmtInf`
+    pub remittance_information: Option<RemittanceInformation>,
+}
+
+/// `AmtDtls`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AmountDetails {
+    /// `InstdAmt`, as ordered by the payer
+    pub instructed: Option<ExchangedAmount>,
+    /// `TxAmt`, as exchanged between the banks
+    pub transaction: Option<ExchangedAmount>,
+    /// `CntrValAmt`, in the currency of the account
+    pub counter_value: Option<ExchangedAmount>,
+}
+
+impl AmountDetails {
+    /// The first of instructed, transaction and counter value amount that
+    /// is not in `currency`, e.g. the USD paid by card from a CHF account.
+    pub fn foreign_amount(&self, currency: &Currency) -> Option<&ExchangedAmount> {
+        [&self.instructed, &self.transaction, &self.counter_value]
+            .into_iter()
+            .flatten()
+            .find(|amount| &amount.amount.currency != currency)
+    }
+
+    /// The first exchange given, `InstdAmt`, `TxAmt` then `CntrValAmt`.
+    pub fn exchange(&self) -> Option<&CurrencyExchange> {
+        [&self.instructed, &self.transaction, &self.counter_value]
+            .into_iter()
+            .flatten()
+            .find_map(|amount| amount.exchange.as_ref())
+    }
+}
+
+/// `InstdAmt`, `TxAmt` or `CntrValAmt`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangedAmount {
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CcyXchg`
+    pub exchange: Option<CurrencyExchange>,
+}
+
+/// `CcyXchg`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrencyExchange {
+    /// `SrcCcy`
+    pub source_currency: Currency,
+    /// `TrgtCcy`
+    pub target_currency: Option<Currency>,
+    /// `UnitCcy`, the currency the rate is quoted per unit of
+    pub unit_currency: Option<Currency>,
+    /// `XchgRate`
+    pub rate: Decimal,
+    /// `CtrctId`
+    pub contract_id: Option<String>,
+    /// `QtnDt`
+    pub quotation_date: Option<NaiveDateTime>,
+}
+
+/// `Chrgs/Rcrd`, or `Chrgs` itself up to version 3
+#[derive(Debug, Clone, PartialEq)]
+pub struct Charge {
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`, a debit when missing
+    pub credit_debit: Option<CreditDebit>,
+    /// `ChrgInclInd`, whether the amount of the entry or transaction
+    /// includes the charge
+    pub included: Option<bool>,
+    /// `Tp/Cd` or `Tp/Prtry/Id`, e.g. `COMM`
+    pub charge_type: Option<String>,
+    /// `Br`, e.g. `DEBT` or `SHAR`
+    pub bearer: Option<String>,
+}
+
+impl Charge {
+    /// The amount, negative for a debit.
+    pub fn signed_amount(&self) -> Decimal {
+        self.amount
+            .signed(self.credit_debit.unwrap_or(CreditDebit::Debit))
+    }
+}
+
+/// `Intrst/Rcrd`, or `Intrst` itself up to version 3
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interest {
+    /// `Amt` with its `Ccy`
+    pub amount: Money,
+    /// `CdtDbtInd`
+    pub credit_debit: CreditDebit,
+    /// `Tp/Cd` or `Tp/Prtry`, e.g. `INDY` or `OVRN`
+    pub interest_type: Option<String>,
+    /// `Rate/Tp/Pctg`, in percent
+    pub rate: Option<Decimal>,
+    /// `FrToDt`
+    pub from_to: Option<DateTimePeriod>,
+    /// `Rsn`
+    pub reason: Option<String>,
+}
+
+/// `Refs`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct References {
+    /// `MsgId`
+    pub message_id: Option<String>,
+    /// `AcctSvcrRef`
+    pub account_servicer_reference: Option<String>,
+    /// `PmtInfId`
+    pub payment_information_id: Option<String>,
+    /// `InstrId`
+    pub instruction_id: Option<String>,
+    /// `EndToEndId`
+    pub end_to_end_id: Option<String>,
+    /// `TxId`
+    pub transaction_id: Option<String>,
+    /// `MndtId`
+    pub mandate_id: Option<String>,
+    /// `ChqNb`
+    pub cheque_number: Option<String>,
+    /// `ClrSysRef`
+    pub clearing_system_reference: Option<String>,
+}
+
+/// `RltdPties`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelatedParties {
+    /// `Dbtr`
+    pub debtor: Option<Party>,
+    /// `DbtrAcct`
+    pub debtor_account: Option<Account>,
+    /// `Cdtr`
+    pub creditor: Option<Party>,
+    /// `CdtrAcct`
+    pub creditor_account: Option<Account>,
+    /// `UltmtDbtr`
+    pub ultimate_debtor: Option<Party>,
+    /// `UltmtCdtr`
+    pub ultimate_creditor: Option<Party>,
+}
+
+/// `Dbtr`, `Cdtr`, `UltmtDbtr` or `UltmtCdtr`, since version 8 the `Pty`
+/// inside them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Party {
+    /// `Nm`
+    pub name: Option<String>,
+    /// `PstlAdr`
+    pub postal_address: Option<PostalAddress>,
+    /// `Id`
+    pub identification: Option<PartyIdentification>,
+    /// `CtryOfRes`
+    pub country_of_residence: Option<String>,
+}
+
+/// `PstlAdr`, structured, in `AdrLine`s, or a mix of both
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PostalAddress {
+    /// `Dept`
+    pub department: Option<String>,
+    /// `StrtNm`
+    pub street_name: Option<String>,
+    /// `BldgNb`
+    pub building_number: Option<String>,
+    /// `PstCd`
+    pub post_code: Option<String>,
+    /// `TwnNm`
+    pub town_name: Option<String>,
+    /// `CtrySubDvsn`
+    pub country_subdivision: Option<String>,
+    /// `Ctry`
+    pub country: Option<String>,
+    /// `AdrLine`, may repeat
+    pub address_lines: Vec<String>,
+}
+
+impl PostalAddress {
+    /// The address on one line, e.g. `Rue du Lac 1, 1000 Lausanne, CH`.
+    pub fn one_line(&self) -> String {
+        let join = |parts: &[&Option<String>]| {
+            parts
+                .iter()
+                .filter_map(|part| part.as_deref())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut lines = vec![
+            join(&[&self.department]),
+            join(&[&self.street_name, &self.building_number]),
+            join(&[&self.post_code, &self.town_name]),
+            join(&[&self.country_subdivision]),
+        ];
+        lines.extend(self.address_lines.iter().cloned());
+        lines.push(join(&[&self.country]));
+        lines.retain(|line| !line.is_empty());
+        lines.join(", ")
+    }
+}
+
+/// `Id` of a party
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartyIdentification {
+    /// `OrgId`
+    Organisation {
+        /// `AnyBIC`, `BICOrBEI` up to version 3
+        bic: Option<String>,
+        /// `LEI`
+        lei: Option<String>,
+        /// `Othr`, may repeat
+        other: Vec<OtherIdentification>,
+    },
+    /// `PrvtId`
+    Private {
+        /// `Othr`, may repeat
+        other: Vec<OtherIdentification>,
+    },
+}
+
+impl PartyIdentification {
+    /// The most telling identifier, e.g. `BIC UBSWCHZH80A` or
+    /// `CHE-123.456.789 (CHID)`.
+    pub fn summary(&self) -> Option<String> {
+        let other = match self {
+            PartyIdentification::Organisation { bic: Some(bic), .. } => {
+                return Some(format!("BIC {}", bic))
+            }
+            PartyIdentification::Organisation { lei: Some(lei), .. } => {
+                return Some(format!("LEI {}", lei))
+            }
+            PartyIdentification::Organisation { other, .. } => other,
+            PartyIdentification::Private { other } => other,
+        };
+        other.first().map(|other| match &other.scheme {
+            Some(scheme) => format!("{} ({})", other.id, scheme),
+            None => other.id.clone(),
+        })
+    }
+}
+
+/// `Othr` of an organisation or private identification
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtherIdentification {
+    /// `Id`
+    pub id: String,
+    /// `SchmeNm/Cd` or `SchmeNm/Prtry`
+    pub scheme: Option<String>,
+    /// `Issr`
+    pub issuer: Option<String>,
+}
+
+/// `RltdAgts`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RelatedAgents {
+    /// `DbtrAgt`
+    pub debtor_agent: Option<FinancialInstitution>,
+    /// `CdtrAgt`
+    pub creditor_agent: Option<FinancialInstitution>,
+}
+
+/// `FinInstnId` of an agent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FinancialInstitution {
+    /// `BICFI`, `BIC` up to version 3
+    pub bic: Option<String>,
+    /// `ClrSysMmbId/MmbId`
+    pub clearing_system_member_id: Option<String>,
+    /// `Nm`
+    pub name: Option<String>,
+}
+
+/// `RmtInf`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemittanceInformation {
+    /// `Ustrd`, may repeat
+    pub unstructured: Vec<String>,
+    /// `Strd`, may repeat
+    pub structured: Vec<StructuredRemittance>,
+}
+
+impl RemittanceInformation {
+    /// The first creditor reference, e.g. the QR-reference of a QR-bill.
+    pub fn creditor_reference(&self) -> Option<&CreditorReference> {
+        self.structured
+            .iter()
+            .find_map(|strd| strd.creditor_reference.as_ref())
+    }
+}
+
+/// `Strd`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructuredRemittance {
+    /// `RfrdDocInf`, may repeat
+    pub referred_documents: Vec<ReferredDocument>,
+    /// `CdtrRefInf`
+    pub creditor_reference: Option<CreditorReference>,
+    /// `AddtlRmtInf`, may repeat
+    pub additional_info: Vec<String>,
+}
+
+/// `RfrdDocInf`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReferredDocument {
+    /// `Tp/CdOrPrtry/Cd` or `Tp/CdOrPrtry/Prtry`, e.g. `CINV`
+    pub document_type: Option<String>,
+    /// `Nb`
+    pub number: Option<String>,
+    /// `RltdDt`
+    pub related_date: Option<NaiveDate>,
+}
+
+/// `CdtrRefInf`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreditorReference {
+    /// `Tp/CdOrPrtry/Cd` or `Tp/CdOrPrtry/Prtry`
+    pub reference_type: Option<CreditorReferenceType>,
+    /// `Tp/Issr`
+    pub issuer: Option<String>,
+    /// `Ref`
+    pub reference: String,
+}
+
+impl CreditorReference {
+    /// Check digits of a QR or ISR reference, or an RF creditor reference,
+    /// `None` for other types, which have no known check.
+    pub fn is_valid(&self) -> Option<bool> {
+        match self.reference_type {
+            Some(CreditorReferenceType::QrReference) => {
+                Some(reference::is_valid_qr_reference(&self.reference))
+            }
+            Some(CreditorReferenceType::Isr) => {
+                Some(reference::is_valid_isr_reference(&self.reference))
+            }
+            Some(CreditorReferenceType::Scor) => {
+                Some(reference::is_valid_creditor_reference(&self.reference))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Type of a `CdtrRefInf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CreditorReferenceType {
+    /// `QRR`, the 27 digit reference of a Swiss QR-bill
+    QrReference,
+    /// `SCOR`, an ISO 11649 `RF` creditor reference
+    Scor,
+    /// `ISR Reference`, of the former Swiss orange payment slip
+    Isr,
+    Other(String),
+}
+
+impl CreditorReferenceType {
+    pub fn from_code(code: &str) -> CreditorReferenceType {
+        match code {
+            "QRR" => CreditorReferenceType::QrReference,
+            "SCOR" => CreditorReferenceType::Scor,
+            "ISR" | "ISR Reference" | "ESR" => CreditorReferenceType::Isr,
+            other => CreditorReferenceType::Other(other.to_string()),
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            CreditorReferenceType::QrReference => "QRR",
+            CreditorReferenceType::Scor => "SCOR",
+            CreditorReferenceType::Isr => "ISR Reference",
+            CreditorReferenceType::Other(code) => code,
+        }
+    }
+}
+
+/// `BkTxCd`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BankTransactionCode {
+    /// `Domn/Cd`
+    pub domain: Option<String>,
+    /// `Domn/Fmly/Cd`
+    pub family: Option<String>,
+    /// `Domn/Fmly/SubFmlyCd`
+    pub sub_family: Option<String>,
+    /// `Prtry/Cd`
+    pub proprietary: Option<String>,
+    /// `Prtry/Issr`
+    pub proprietary_issuer: Option<String>,
+}
+
+impl BankTransactionCode {
+    /// `Domn/Fmly/SubFmlyCd` as in `PMNT/RCDT/ESCT`, or else the
+    /// proprietary code.
+    pub fn code(&self) -> Option<String> {
+        match &self.domain {
+            Some(domain) => {
+                let codes = [Some(domain), self.family.as_ref(), self.sub_family.as_ref()];
+                Some(
+                    codes
+                        .into_iter()
+                        .flatten()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                )
+            }
+            None => self.proprietary.clone(),
+        }
+    }
+
+    /// Readable label of the ISO code, e.g. "SEPA credit transfer received",
+    /// `None` for proprietary codes and unknown domains.
+    pub fn label(&self) -> Option<String> {
+        transaction_code::label(
+            self.domain.as_deref()?,
+            self.family.as_deref(),
+            self.sub_family.as_deref(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{balance, entry, message, parse, statement};
+
+    /// Page `page` of a camt.053 message made of `statements`.
+    fn page(page: u32, last_page: bool, statements: &[String]) -> Document {
+        let pagination = format!(
+            "<MsgPgntn><PgNb>{}</PgNb><LastPgInd>{}</LastPgInd></MsgPgntn>",
+            page, last_page
+        );
+        parse(&message("camt.053.001.04", &pagination, statements))
+    }
+
+    #[test]
+    fn pages_are_joined() {
+        let first = page(
+            1,
+            false,
+            &[statement(
+                "S1",
+                1,
+                1,
+                31,
+                &[
+                    balance("OPBD", "100.00"),
+                    entry("10.00", "CRDT", "BOOK", "A1", ""),
+                ]
+                .concat(),
+            )],
+        );
+        let second = || {
+            page(
+                2,
+                true,
+                &[statement(
+                    "S1",
+                    1,
+                    1,
+                    31,
+                    &[
+                        entry("20.00", "CRDT", "BOOK", "A2", ""),
+                        balance("CLBD", "130.00"),
+                    ]
+                    .concat(),
+                )],
+            )
+        };
+        assert_eq!(
+            first.group_header.pagination.as_ref().unwrap().page_number,
+            1
+        );
+        assert!(second().group_header.pagination.unwrap().last_page);
+
+        // the second page comes twice
+        let statements = merge_documents([first, second(), second()]);
+        assert_eq!(statements.len(), 1);
+        let references: Vec<_> = statements[0]
+            .entries
+            .iter()
+            .map(|entry| entry.account_servicer_reference.as_deref().unwrap())
+            .collect();
+        assert_eq!(references, ["A1", "A2"]);
+        assert!(statements[0].balance(&BalanceType::Opening).is_some());
+        assert!(statements[0].balance(&BalanceType::Closing).is_some());
+    }
+
+    #[test]
+    fn statements_are_kept_apart() {
+        let other_account = statement("S1", 1, 1, 31, &entry("30.00", "CRDT", "BOOK", "B1", ""))
+            .replace("CH9300762011623852957", "CH5604835012345678009");
+        let unpaginated = parse(&message(
+            "camt.053.001.04",
+            "",
+            &[statement("S2", 2, 1, 31, "")],
+        ));
+        let statements = merge_documents([
+            page(1, false, &[statement("S1", 1, 1, 31, "")]),
+            page(2, true, &[other_account]),
+            unpaginated,
+        ]);
+        let ids: Vec<_> = statements
+            .iter()
+            .map(|statement| (statement.id.as_str(), statement.entries.len()))
+            .collect();
+        assert_eq!(ids, [("S1", 0), ("S1", 1), ("S2", 0)]);
+    }
+}
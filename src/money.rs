@@ -0,0 +1,100 @@
+//! Exact amounts with their ISO 4217 currency.
+
+use crate::model::CreditDebit;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// An ISO 4217 currency code such as `CHF`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Currency(String);
+
+impl Currency {
+    /// Accepts any three uppercase letters, unknown codes get 2 minor units.
+    pub fn new(code: &str) -> Option<Currency> {
+        if code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase()) {
+            Some(Currency(code.to_string()))
+        } else {
+            None
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+
+    /// Number of decimals of the currency, e.g. 2 for CHF, 0 for JPY.
+    pub fn minor_units(&self) -> u32 {
+        match self.code() {
+            "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF"
+            | "UGX" | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+            "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+            "CLF" | "UYW" => 4,
+            _ => 2,
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An exact, non rounded, amount of money.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    /// `None` if `amount` has more decimals than the currency allows.
+    pub fn new(amount: Decimal, currency: Currency) -> Option<Money> {
+        if amount.normalize().scale() > currency.minor_units() {
+            return None;
+        }
+        Some(Money { amount, currency })
+    }
+
+    pub fn zero(currency: Currency) -> Money {
+        Money {
+            amount: Decimal::ZERO,
+            currency,
+        }
+    }
+
+    /// Sum of two amounts, `None` if the currencies differ.
+    pub fn checked_add(&self, other: &Money) -> Option<Money> {
+        if self.currency != other.currency {
+            return None;
+        }
+        Some(Money {
+            amount: self.amount + other.amount,
+            currency: self.currency.clone(),
+        })
+    }
+
+    /// The amount, negative for a debit.
+    pub fn signed(&self, credit_debit: CreditDebit) -> Decimal {
+        match credit_debit {
+            CreditDebit::Credit => self.amount,
+            CreditDebit::Debit => -self.amount,
+        }
+    }
+
+    /// The amount written with exactly the minor units of the currency,
+    /// e.g. `150.00` for `150` CHF.
+    pub fn amount_string(&self) -> String {
+        let mut amount = self.amount;
+        if amount.scale() < self.currency.minor_units() {
+            amount.rescale(self.currency.minor_units());
+        }
+        amount.to_string()
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount_string(), self.currency)
+    }
+}
@@ -0,0 +1,1450 @@
+use crate::error::{CamtError, Diagnostics, Location};
+use crate::model::{
+    Account, AccountProxy, AmountDetails, Availability, AvailabilityDate, Balance, BalanceType,
+    BankTransactionCode, Charge, CreditDebit, CreditorReference, CreditorReferenceType,
+    CurrencyExchange, DateAndDateTime, DateTimePeriod, Document, Entry, EntryDetails, EntryStatus,
+    ExchangedAmount, FinancialInstitution, GroupHeader, Interest, MessageKind, NumberAndSum,
+    OtherAccount, OtherIdentification, Pagination, Party, PartyIdentification, PostalAddress,
+    References, ReferredDocument, RelatedAgents, RelatedParties, RemittanceInformation, Statement,
+    StructuredRemittance, TransactionDetails, TransactionsSummary,
+};
+use crate::money::{Currency, Money};
+use crate::version::SchemaVersion;
+use crate::ParseOptions;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use minidom::Element;
+use minidom::NSChoice::Any as NSAny;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Follow a `/` separated path of child names below `element`.
+fn find<'a>(element: &'a Element, rel: &str) -> Option<&'a Element> {
+    rel.split('/')
+        .try_fold(element, |container, name| container.get_child(name, NSAny))
+}
+
+/// Like [`find`], but a missing element is an error.
+fn required<'a>(
+    element: &'a Element,
+    rel: &str,
+    location: &Location,
+) -> Result<&'a Element, CamtError> {
+    find(element, rel).ok_or_else(|| CamtError::MissingElement {
+        location: location.join(rel),
+    })
+}
+
+/// Text of an optional element.
+fn text(element: &Element, rel: &str) -> Option<String> {
+    find(element, rel).map(|element| element.text())
+}
+
+/// Amount and currency of an `Amt` element like `<Amt Ccy="CHF">12.50</Amt>`.
+///
+/// The amount must not have more decimals than the currency allows.
+fn amount(element: &Element, location: &Location) -> Result<Money, CamtError> {
+    let value = element.text();
+    let code = element
+        .attr("Ccy")
+        .ok_or_else(|| CamtError::MissingElement {
+            location: location.join("@Ccy"),
+        })?;
+    let currency = Currency::new(code).ok_or_else(|| CamtError::BadCode {
+        value: code.to_string(),
+        location: location.join("@Ccy"),
+    })?;
+    Decimal::from_str(&value)
+        .ok()
+        .filter(|amount| amount.is_sign_positive())
+        .and_then(|amount| Money::new(amount, currency))
+        .ok_or_else(|| CamtError::BadAmount {
+            value,
+            location: location.clone(),
+        })
+}
+
+/// `CRDT` or `DBIT` of a `CdtDbtInd` element.
+fn credit_debit(element: &Element, location: &Location) -> Result<CreditDebit, CamtError> {
+    let value = element.text();
+    CreditDebit::from_code(&value).ok_or_else(|| CamtError::BadCode {
+        value,
+        location: location.clone(),
+    })
+}
+
+/// An ISO date (`YYYY-MM-DD`).
+fn date(element: &Element, location: &Location) -> Result<NaiveDate, CamtError> {
+    let value = element.text();
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| CamtError::BadDate {
+        value,
+        location: location.clone(),
+    })
+}
+
+/// An ISO datetime, with or without fraction and offset. The time is kept
+/// as written, the offset is dropped.
+fn datetime(element: &Element, location: &Location) -> Result<NaiveDateTime, CamtError> {
+    let value = element.text();
+    DateTime::parse_from_rfc3339(&value)
+        .map(|datetime| datetime.naive_local())
+        .or_else(|_| NaiveDateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .map_err(|_| CamtError::BadDate {
+            value,
+            location: location.clone(),
+        })
+}
+
+/// A `BookgDt`, `ValDt` or `Bal/Dt` element holding either `Dt` or `DtTm`.
+fn date_and_datetime(element: &Element, location: &Location) -> Result<DateAndDateTime, CamtError> {
+    if let Some(dt) = find(element, "Dt") {
+        return Ok(DateAndDateTime::Date(date(dt, &location.join("Dt"))?));
+    }
+    if let Some(dt_tm) = find(element, "DtTm") {
+        return Ok(DateAndDateTime::DateTime(datetime(
+            dt_tm,
+            &location.join("DtTm"),
+        )?));
+    }
+    Err(CamtError::MissingElement {
+        location: location.join("Dt"),
+    })
+}
+
+/// Extract the statements and their entries from a parsed camt.053
+/// document, or the reports or notifications of a camt.052 or camt.054 one.
+pub fn process_camt53(
+    root_element: &Element,
+    options: &ParseOptions,
+) -> Result<Document, CamtError> {
+    let location = Location::new(root_element.name());
+
+    // Parse the XML content
+    let (kind, customer_statment) = MessageKind::ALL
+        .iter()
+        .find_map(|kind| {
+            root_element
+                .get_child(kind.root(), NSAny)
+                .map(|element| (*kind, element))
+        })
+        .ok_or_else(|| CamtError::UnsupportedMessage {
+            message: root_element
+                .children()
+                .next()
+                .map(|child| child.name().to_string())
+                .unwrap_or_default(),
+            location: location.clone(),
+        })?;
+    let location = location.join(kind.root());
+
+    // the namespace tells the schema version, unknown ones are parsed as
+    // well as possible
+    let namespace = root_element.ns();
+    let version = SchemaVersion::from_namespace(&namespace);
+    if let Some(version) = version.filter(|version| version.kind != kind) {
+        return Err(CamtError::UnsupportedMessage {
+            message: format!("{} namespace with {} content", version, kind.name()),
+            location,
+        });
+    }
+
+    let group_header = required(customer_statment, "GrpHdr", &location)?;
+    let header_location = location.join("GrpHdr");
+    let group_header = GroupHeader {
+        message_id: required(group_header, "MsgId", &header_location)?.text(),
+        creation_date_time: datetime(
+            required(group_header, "CreDtTm", &header_location)?,
+            &header_location.join("CreDtTm"),
+        )?,
+        pagination: find(group_header, "MsgPgntn")
+            .map(|pgntn| pagination_parser(pgntn, &header_location.join("MsgPgntn")))
+            .transpose()?,
+    };
+
+    // a message may hold several statements, e.g. one per account
+    let mut diagnostics = Diagnostics::new(options.lenient);
+    let mut statements = Vec::new();
+    for stmt in customer_statment.children() {
+        if stmt.is(kind.item(), NSAny) {
+            let stmt_location =
+                location.join(&format!("{}[{}]", kind.item(), statements.len() + 1));
+            statements.push(stmt_parser(kind, stmt, &stmt_location, &mut diagnostics)?);
+        }
+    }
+    if statements.is_empty() {
+        return Err(CamtError::MissingElement {
+            location: location.join(kind.item()),
+        });
+    }
+
+    Ok(Document {
+        kind,
+        namespace,
+        version,
+        group_header,
+        statements,
+        diagnostics: diagnostics.skipped,
+    })
+}
+
+/// A counter such as `ElctrncSeqNb`.
+fn sequence_number(element: &Element, location: &Location) -> Result<u64, CamtError> {
+    let value = element.text();
+    value.parse::<u64>().map_err(|_| CamtError::BadNumber {
+        value,
+        location: location.clone(),
+    })
+}
+
+/// Parse a `MsgPgntn` element.
+fn pagination_parser(pgntn: &Element, location: &Location) -> Result<Pagination, CamtError> {
+    let page_number = required(pgntn, "PgNb", location)?.text();
+    Ok(Pagination {
+        page_number: page_number
+            .parse::<u32>()
+            .map_err(|_| CamtError::BadNumber {
+                value: page_number.clone(),
+                location: location.join("PgNb"),
+            })?,
+        last_page: required(pgntn, "LastPgInd", location)?.text() == "true",
+    })
+}
+
+/// Parse a `FrToDt` element.
+fn from_to_parser(fr_to_dt: &Element, location: &Location) -> Result<DateTimePeriod, CamtError> {
+    Ok(DateTimePeriod {
+        from: datetime(
+            required(fr_to_dt, "FrDtTm", location)?,
+            &location.join("FrDtTm"),
+        )?,
+        to: datetime(
+            required(fr_to_dt, "ToDtTm", location)?,
+            &location.join("ToDtTm"),
+        )?,
+    })
+}
+
+/// Parse one `Stmt` (or `Rpt`, `Ntfctn`) element with its balances and entries.
+fn stmt_parser(
+    kind: MessageKind,
+    stmt: &Element,
+    location: &Location,
+    diagnostics: &mut Diagnostics,
+) -> Result<Statement, CamtError> {
+    // create other data to collect
+    let mut statement = Statement {
+        kind,
+        id: required(stmt, "Id", location)?.text(),
+        ..Default::default()
+    };
+    let mut ntry_index = 0;
+
+    // iterate over statement children and process according to type
+    for child in stmt.children() {
+        // data about statment
+        if child.is("ElctrncSeqNb", NSAny) {
+            statement.electronic_sequence_number =
+                Some(sequence_number(child, &location.join("ElctrncSeqNb"))?);
+        }
+
+        if child.is("LglSeqNb", NSAny) {
+            statement.legal_sequence_number =
+                Some(sequence_number(child, &location.join("LglSeqNb"))?);
+        }
+
+        if child.is("FrToDt", NSAny) {
+            statement.from_to = Some(from_to_parser(child, &location.join("FrToDt"))?);
+        }
+
+        if child.is("CreDtTm", NSAny) {
+            statement.creation_date_time = Some(datetime(child, &location.join("CreDtTm"))?);
+        }
+
+        // data about account
+        if child.is("Acct", NSAny) {
+            statement.account = account_parser(child);
+            if statement.account.identifier().is_none() {
+                return Err(CamtError::MissingElement {
+                    location: location.join("Acct/Id"),
+                });
+            }
+        }
+
+        if child.is("TxsSummry", NSAny) {
+            statement.summary = Some(summary_parser(child, &location.join("TxsSummry"))?);
+        }
+
+        if child.is("Bal", NSAny) {
+            let bal_location = location.join(&format!("Bal[{}]", statement.balances.len() + 1));
+            statement.balances.push(bal_parser(child, &bal_location)?);
+        }
+
+        // entries
+        if child.is("Ntry", NSAny) {
+            ntry_index += 1;
+            let res = ntry_parser(child, &location.entry(ntry_index), diagnostics);
+            if let Some(entry) = diagnostics.recover(res, child)? {
+                statement.entries.push(entry);
+            }
+        }
+    }
+    Ok(statement)
+}
+
+/// Parse one `Bal` element.
+fn bal_parser(bal: &Element, location: &Location) -> Result<Balance, CamtError> {
+    let amount = amount(required(bal, "Amt", location)?, &location.join("Amt"))?;
+    Ok(Balance {
+        balance_type: BalanceType::from_code(
+            &text(bal, "Tp/CdOrPrtry/Cd")
+                .or_else(|| text(bal, "Tp/CdOrPrtry/Prtry"))
+                .unwrap_or_default(),
+        ),
+        amount,
+        credit_debit: credit_debit(
+            required(bal, "CdtDbtInd", location)?,
+            &location.join("CdtDbtInd"),
+        )?,
+        date: find(bal, "Dt")
+            .map(|element| date_and_datetime(element, &location.join("Dt")))
+            .transpose()?,
+    })
+}
+
+/// A plain decimal, as in `TxsSummry/*/Sum`.
+fn decimal(element: &Element, location: &Location) -> Result<Decimal, CamtError> {
+    let value = element.text();
+    Decimal::from_str(&value).map_err(|_| CamtError::BadAmount {
+        value,
+        location: location.clone(),
+    })
+}
+
+/// A decimal and `CdtDbtInd` pair below `element`, negative for a debit.
+fn signed_decimal(
+    element: &Element,
+    amount_path: &str,
+    indicator_path: &str,
+    location: &Location,
+) -> Result<Decimal, CamtError> {
+    let amount = decimal(
+        required(element, amount_path, location)?,
+        &location.join(amount_path),
+    )?;
+    let indicator = credit_debit(
+        required(element, indicator_path, location)?,
+        &location.join(indicator_path),
+    )?;
+    Ok(match indicator {
+        CreditDebit::Credit => amount,
+        CreditDebit::Debit => -amount,
+    })
+}
+
+/// Parse the `NbOfNtries` and `Sum` of a `TxsSummry` total.
+fn number_and_sum_parser(total: &Element, location: &Location) -> Result<NumberAndSum, CamtError> {
+    Ok(NumberAndSum {
+        number_of_entries: find(total, "NbOfNtries")
+            .map(|element| {
+                let value = element.text();
+                value.parse::<u64>().map_err(|_| CamtError::BadNumber {
+                    value: value.clone(),
+                    location: location.join("NbOfNtries"),
+                })
+            })
+            .transpose()?,
+        sum: find(total, "Sum")
+            .map(|element| decimal(element, &location.join("Sum")))
+            .transpose()?,
+    })
+}
+
+/// Parse a `TxsSummry` element.
+fn summary_parser(
+    summary: &Element,
+    location: &Location,
+) -> Result<TransactionsSummary, CamtError> {
+    let total_location = location.join("TtlNtries");
+    // TtlNtries/TtlNetNtry since version 3, TtlNetNtryAmt and
+    // TtlNetNtryCdtDbtInd before
+    let total_net = match find(summary, "TtlNtries/TtlNetNtry") {
+        Some(net) => Some(signed_decimal(
+            net,
+            "Amt",
+            "CdtDbtInd",
+            &total_location.join("TtlNetNtry"),
+        )?),
+        None => match find(summary, "TtlNtries") {
+            Some(total) if find(total, "TtlNetNtryAmt").is_some() => Some(signed_decimal(
+                total,
+                "TtlNetNtryAmt",
+                "TtlNetNtryCdtDbtInd",
+                &total_location,
+            )?),
+            _ => None,
+        },
+    };
+
+    Ok(TransactionsSummary {
+        total: find(summary, "TtlNtries")
+            .map(|total| number_and_sum_parser(total, &total_location))
+            .transpose()?,
+        total_net,
+        total_credit: find(summary, "TtlCdtNtries")
+            .map(|total| number_and_sum_parser(total, &location.join("TtlCdtNtries")))
+            .transpose()?,
+        total_debit: find(summary, "TtlDbtNtries")
+            .map(|total| number_and_sum_parser(total, &location.join("TtlDbtNtries")))
+            .transpose()?,
+    })
+}
+
+/// Parse a `BkTxCd` element.
+fn bktxcd_parser(bk_tx_cd: &Element) -> BankTransactionCode {
+    BankTransactionCode {
+        domain: text(bk_tx_cd, "Domn/Cd"),
+        family: text(bk_tx_cd, "Domn/Fmly/Cd"),
+        sub_family: text(bk_tx_cd, "Domn/Fmly/SubFmlyCd"),
+        proprietary: text(bk_tx_cd, "Prtry/Cd"),
+        proprietary_issuer: text(bk_tx_cd, "Prtry/Issr"),
+    }
+}
+
+/// `Sts`, a plain code or, since version 7, a `Cd`/`Prtry` choice. Banks
+/// do not always follow their declared version, so the shape of the
+/// element decides.
+fn status(sts: &Element) -> EntryStatus {
+    let code = text(sts, "Cd").or_else(|| text(sts, "Prtry"));
+    EntryStatus::from_code(code.unwrap_or_else(|| sts.text()).trim())
+}
+
+/// Parse one `Ntry` element with its transaction details.
+///
+/// `location` is the position of the `Ntry` element, used in errors. A
+/// `TxDtls` that fails to parse goes through `diagnostics`, so in lenient
+/// mode only that transaction is dropped.
+pub fn ntry_parser(
+    child: &Element,
+    location: &Location,
+    diagnostics: &mut Diagnostics,
+) -> Result<Entry, CamtError> {
+    // get amount of entry
+    let amount = amount(required(child, "Amt", location)?, &location.join("Amt"))?;
+
+    // get booking date, which will be used a reference date
+    let booking_date = date_and_datetime(
+        required(child, "BookgDt", location)?,
+        &location.join("BookgDt"),
+    )?;
+    let value_date = find(child, "ValDt")
+        .map(|element| date_and_datetime(element, &location.join("ValDt")))
+        .transpose()?;
+    let mut availability = Vec::new();
+    let avlbtys = child.children().filter(|child| child.is("Avlbty", NSAny));
+    for (index, avlbty) in avlbtys.enumerate() {
+        let location = location.join(&format!("Avlbty[{}]", index + 1));
+        availability.push(availability_parser(avlbty, &location)?);
+    }
+
+    // get type of booking
+    let credit_debit = credit_debit(
+        required(child, "CdtDbtInd", location)?,
+        &location.join("CdtDbtInd"),
+    )?;
+
+    let mut entry = Entry {
+        reference: text(child, "NtryRef"),
+        amount,
+        credit_debit,
+        amount_details: find(child, "AmtDtls")
+            .map(|amt_dtls| amount_details_parser(amt_dtls, &location.join("AmtDtls")))
+            .transpose()?,
+        status: find(child, "Sts").map(status),
+        booking_date,
+        value_date,
+        availability,
+        account_servicer_reference: text(child, "AcctSvcrRef"),
+        bank_transaction_code: find(child, "BkTxCd").map(bktxcd_parser),
+        charges: charges_parser(child, location)?,
+        interest: interest_parser(child, location)?,
+        details: Vec::new(),
+        skipped_transactions: 0,
+        // get NTry description, optional in the schema
+        additional_info: text(child, "AddtlNtryInf"),
+    };
+
+    let transactions = child
+        .children()
+        .filter(|ntry_dtls| ntry_dtls.is("NtryDtls", NSAny))
+        .flat_map(|ntry_dtls| ntry_dtls.children())
+        .filter(|tx_dtls| tx_dtls.is("TxDtls", NSAny))
+        .count();
+    let mut tx_index = 0;
+    for ntry_dtls in child.children() {
+        if ntry_dtls.is("NtryDtls", NSAny) {
+            let mut details = EntryDetails::default();
+            for ntry_dtls_child in ntry_dtls.children() {
+                if ntry_dtls_child.is("TxDtls", NSAny) {
+                    tx_index += 1;
+                    let tx_location = location.join(&format!("NtryDtls/TxDtls[{}]", tx_index));
+                    let txdtls =
+                        txdtls_parser(ntry_dtls_child, &entry, transactions == 1, &tx_location);
+                    match diagnostics.recover(txdtls, ntry_dtls_child)? {
+                        Some(txdtls) => details.transactions.push(txdtls),
+                        None => entry.skipped_transactions += 1,
+                    }
+                }
+            }
+            entry.details.push(details);
+        }
+    }
+
+    Ok(entry)
+}
+
+/// A currency code element, e.g. `SrcCcy`.
+fn currency(element: &Element, location: &Location) -> Result<Currency, CamtError> {
+    let value = element.text();
+    Currency::new(&value).ok_or_else(|| CamtError::BadCode {
+        value,
+        location: location.clone(),
+    })
+}
+
+/// Parse an `AmtDtls` element.
+fn amount_details_parser(
+    amt_dtls: &Element,
+    location: &Location,
+) -> Result<AmountDetails, CamtError> {
+    let exchanged_amount = |name: &str| -> Result<Option<ExchangedAmount>, CamtError> {
+        let element = match find(amt_dtls, name) {
+            Some(element) => element,
+            None => return Ok(None),
+        };
+        let location = location.join(name);
+        let exchange = match find(element, "CcyXchg") {
+            Some(ccy_xchg) => {
+                let location = location.join("CcyXchg");
+                let optional_currency = |name: &str| {
+                    find(ccy_xchg, name)
+                        .map(|element| currency(element, &location.join(name)))
+                        .transpose()
+                };
+                Some(CurrencyExchange {
+                    source_currency: currency(
+                        required(ccy_xchg, "SrcCcy", &location)?,
+                        &location.join("SrcCcy"),
+                    )?,
+                    target_currency: optional_currency("TrgtCcy")?,
+                    unit_currency: optional_currency("UnitCcy")?,
+                    rate: decimal(
+                        required(ccy_xchg, "XchgRate", &location)?,
+                        &location.join("XchgRate"),
+                    )?,
+                    contract_id: text(ccy_xchg, "CtrctId"),
+                    quotation_date: find(ccy_xchg, "QtnDt")
+                        .map(|element| datetime(element, &location.join("QtnDt")))
+                        .transpose()?,
+                })
+            }
+            None => None,
+        };
+        Ok(Some(ExchangedAmount {
+            amount: amount(required(element, "Amt", &location)?, &location.join("Amt"))?,
+            exchange,
+        }))
+    };
+
+    Ok(AmountDetails {
+        instructed: exchanged_amount("InstdAmt")?,
+        transaction: exchanged_amount("TxAmt")?,
+        counter_value: exchanged_amount("CntrValAmt")?,
+    })
+}
+
+/// The records of a `Chrgs` or `Intrst` element of `parent`. Since version 4
+/// they are `Rcrd` children of a single element, before that the element
+/// itself repeats.
+fn records<'a>(parent: &'a Element, name: &str) -> Vec<(&'a Element, String)> {
+    let mut records = Vec::new();
+    for (index, element) in parent
+        .children()
+        .filter(|child| child.is(name, NSAny))
+        .enumerate()
+    {
+        let rcrds: Vec<&Element> = element
+            .children()
+            .filter(|child| child.is("Rcrd", NSAny))
+            .collect();
+        if rcrds.is_empty() {
+            records.push((element, format!("{}[{}]", name, index + 1)));
+        }
+        for (rcrd_index, rcrd) in rcrds.into_iter().enumerate() {
+            records.push((rcrd, format!("{}/Rcrd[{}]", name, rcrd_index + 1)));
+        }
+    }
+    records
+}
+
+/// Parse the charges of an entry or transaction.
+fn charges_parser(parent: &Element, location: &Location) -> Result<Vec<Charge>, CamtError> {
+    let mut charges = Vec::new();
+    for (rcrd, path) in records(parent, "Chrgs") {
+        let location = location.join(&path);
+        let included = match find(rcrd, "ChrgInclInd") {
+            Some(element) => Some(match element.text().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(CamtError::BadCode {
+                        value: element.text(),
+                        location: location.join("ChrgInclInd"),
+                    })
+                }
+            }),
+            None => None,
+        };
+        charges.push(Charge {
+            amount: amount(required(rcrd, "Amt", &location)?, &location.join("Amt"))?,
+            credit_debit: find(rcrd, "CdtDbtInd")
+                .map(|element| credit_debit(element, &location.join("CdtDbtInd")))
+                .transpose()?,
+            included,
+            charge_type: text(rcrd, "Tp/Cd").or_else(|| text(rcrd, "Tp/Prtry/Id")),
+            bearer: text(rcrd, "Br"),
+        });
+    }
+    Ok(charges)
+}
+
+/// Parse the interest of an entry or transaction.
+fn interest_parser(parent: &Element, location: &Location) -> Result<Vec<Interest>, CamtError> {
+    let mut interest = Vec::new();
+    for (rcrd, path) in records(parent, "Intrst") {
+        let location = location.join(&path);
+        interest.push(Interest {
+            amount: amount(required(rcrd, "Amt", &location)?, &location.join("Amt"))?,
+            credit_debit: credit_debit(
+                required(rcrd, "CdtDbtInd", &location)?,
+                &location.join("CdtDbtInd"),
+            )?,
+            interest_type: text(rcrd, "Tp/Cd").or_else(|| text(rcrd, "Tp/Prtry")),
+            rate: find(rcrd, "Rate/Tp/Pctg")
+                .map(|element| decimal(element, &location.join("Rate/Tp/Pctg")))
+                .transpose()?,
+            from_to: find(rcrd, "FrToDt")
+                .map(|element| from_to_parser(element, &location.join("FrToDt")))
+                .transpose()?,
+            reason: text(rcrd, "Rsn"),
+        });
+    }
+    Ok(interest)
+}
+
+/// Parse an `Avlbty` element.
+fn availability_parser(avlbty: &Element, location: &Location) -> Result<Availability, CamtError> {
+    let date = match (find(avlbty, "Dt/NbOfDays"), find(avlbty, "Dt/ActlDt")) {
+        (Some(nb_of_days), _) => {
+            let value = nb_of_days.text();
+            let days = value
+                .strip_prefix('+')
+                .unwrap_or(&value)
+                .parse()
+                .map_err(|_| CamtError::BadNumber {
+                    value: value.clone(),
+                    location: location.join("Dt/NbOfDays"),
+                })?;
+            AvailabilityDate::Days(days)
+        }
+        (None, Some(actl_dt)) => {
+            AvailabilityDate::Date(date(actl_dt, &location.join("Dt/ActlDt"))?)
+        }
+        (None, None) => {
+            return Err(CamtError::MissingElement {
+                location: location.join("Dt/ActlDt"),
+            })
+        }
+    };
+    Ok(Availability {
+        date,
+        amount: amount(required(avlbty, "Amt", location)?, &location.join("Amt"))?,
+        credit_debit: credit_debit(
+            required(avlbty, "CdtDbtInd", location)?,
+            &location.join("CdtDbtInd"),
+        )?,
+    })
+}
+
+/// Parse a `Dbtr`/`Cdtr` element.
+fn party_parser(party: &Element) -> Party {
+    // since version 8 a party or an agent, only parties are kept
+    let party = find(party, "Pty").unwrap_or(party);
+    let postal_address = find(party, "PstlAdr").map(|pstl_adr| PostalAddress {
+        department: text(pstl_adr, "Dept"),
+        street_name: text(pstl_adr, "StrtNm"),
+        building_number: text(pstl_adr, "BldgNb"),
+        post_code: text(pstl_adr, "PstCd"),
+        town_name: text(pstl_adr, "TwnNm"),
+        country_subdivision: text(pstl_adr, "CtrySubDvsn"),
+        country: text(pstl_adr, "Ctry"),
+        address_lines: pstl_adr
+            .children()
+            .filter(|child| child.is("AdrLine", NSAny))
+            .map(|adr_line| adr_line.text())
+            .collect(),
+    });
+
+    let other = |id: &Element| -> Vec<OtherIdentification> {
+        id.children()
+            .filter(|child| child.is("Othr", NSAny))
+            .filter_map(|othr| {
+                Some(OtherIdentification {
+                    id: text(othr, "Id")?,
+                    scheme: text(othr, "SchmeNm/Cd").or_else(|| text(othr, "SchmeNm/Prtry")),
+                    issuer: text(othr, "Issr"),
+                })
+            })
+            .collect()
+    };
+    let identification = match (find(party, "Id/OrgId"), find(party, "Id/PrvtId")) {
+        (Some(org_id), _) => Some(PartyIdentification::Organisation {
+            bic: text(org_id, "AnyBIC").or_else(|| text(org_id, "BICOrBEI")),
+            lei: text(org_id, "LEI"),
+            other: other(org_id),
+        }),
+        (None, Some(prvt_id)) => Some(PartyIdentification::Private {
+            other: other(prvt_id),
+        }),
+        (None, None) => None,
+    };
+
+    Party {
+        name: text(party, "Nm"),
+        postal_address,
+        identification,
+        country_of_residence: text(party, "CtryOfRes"),
+    }
+}
+
+/// Parse the `FinInstnId` of a `DbtrAgt`/`CdtrAgt` element.
+fn agent_parser(agent: &Element) -> FinancialInstitution {
+    FinancialInstitution {
+        bic: text(agent, "FinInstnId/BICFI").or_else(|| text(agent, "FinInstnId/BIC")),
+        clearing_system_member_id: text(agent, "FinInstnId/ClrSysMmbId/MmbId"),
+        name: text(agent, "FinInstnId/Nm"),
+    }
+}
+
+/// Parse an `Acct`, `DbtrAcct` or `CdtrAcct` element.
+fn account_parser(account: &Element) -> Account {
+    Account {
+        iban: text(account, "Id/IBAN"),
+        other: find(account, "Id/Othr").and_then(|othr| {
+            Some(OtherAccount {
+                id: text(othr, "Id")?,
+                scheme: text(othr, "SchmeNm/Cd").or_else(|| text(othr, "SchmeNm/Prtry")),
+                issuer: text(othr, "Issr"),
+            })
+        }),
+        proxy: find(account, "Prxy").and_then(|prxy| {
+            Some(AccountProxy {
+                proxy_type: text(prxy, "Tp/Cd").or_else(|| text(prxy, "Tp/Prtry")),
+                id: text(prxy, "Id")?,
+            })
+        }),
+        name: text(account, "Nm"),
+        currency: text(account, "Ccy").and_then(|code| Currency::new(&code)),
+    }
+}
+
+/// Parse a `RmtInf` element, unstructured and structured.
+fn rmtinf_parser(
+    rmt_inf: &Element,
+    location: &Location,
+) -> Result<RemittanceInformation, CamtError> {
+    let mut structured = Vec::new();
+    let strds = rmt_inf.children().filter(|child| child.is("Strd", NSAny));
+    for (index, strd) in strds.enumerate() {
+        let location = location.join(&format!("Strd[{}]", index + 1));
+        let mut referred_documents = Vec::new();
+        for rfrd_doc_inf in strd
+            .children()
+            .filter(|child| child.is("RfrdDocInf", NSAny))
+        {
+            // RltdDt is a date up to version 7, a Tp/Dt pair since
+            let related_date =
+                match find(rfrd_doc_inf, "RltdDt/Dt").or_else(|| find(rfrd_doc_inf, "RltdDt")) {
+                    Some(rltd_dt) => Some(date(rltd_dt, &location.join("RfrdDocInf/RltdDt"))?),
+                    None => None,
+                };
+            referred_documents.push(ReferredDocument {
+                document_type: text(rfrd_doc_inf, "Tp/CdOrPrtry/Cd")
+                    .or_else(|| text(rfrd_doc_inf, "Tp/CdOrPrtry/Prtry")),
+                number: text(rfrd_doc_inf, "Nb"),
+                related_date,
+            });
+        }
+        let creditor_reference = match find(strd, "CdtrRefInf") {
+            Some(cdtr_ref_inf) => Some(CreditorReference {
+                reference_type: text(cdtr_ref_inf, "Tp/CdOrPrtry/Cd")
+                    .or_else(|| text(cdtr_ref_inf, "Tp/CdOrPrtry/Prtry"))
+                    .map(|code| CreditorReferenceType::from_code(&code)),
+                issuer: text(cdtr_ref_inf, "Tp/Issr"),
+                reference: required(cdtr_ref_inf, "Ref", &location.join("CdtrRefInf"))?.text(),
+            }),
+            None => None,
+        };
+        structured.push(StructuredRemittance {
+            referred_documents,
+            creditor_reference,
+            additional_info: strd
+                .children()
+                .filter(|child| child.is("AddtlRmtInf", NSAny))
+                .map(|addtl_rmt_inf| addtl_rmt_inf.text())
+                .collect(),
+        });
+    }
+
+    Ok(RemittanceInformation {
+        unstructured: rmt_inf
+            .children()
+            .filter(|child| child.is("Ustrd", NSAny))
+            .map(|ustrd| ustrd.text())
+            .collect(),
+        structured,
+    })
+}
+
+/// Parse one `TxDtls` element of `entry`, `only_transaction` when it is
+/// the single `TxDtls` of the entry.
+///
+/// `location` is the position of the `TxDtls` element, used in errors.
+pub fn txdtls_parser(
+    tx_dtls: &Element,
+    entry: &Entry,
+    only_transaction: bool,
+    location: &Location,
+) -> Result<TransactionDetails, CamtError> {
+    // amount and type of transaction, both optional: version 2 has the
+    // amount in AmtDtls only, later versions may leave both out, then the
+    // single transaction of an entry is the entry itself
+    let amount = match ["Amt", "AmtDtls/TxAmt/Amt"]
+        .into_iter()
+        .find_map(|path| find(tx_dtls, path).map(|element| (element, path)))
+    {
+        Some((element, path)) => amount(element, &location.join(path))?,
+        None if only_transaction => entry.amount.clone(),
+        None => {
+            return Err(CamtError::MissingElement {
+                location: location.join("Amt"),
+            })
+        }
+    };
+    let credit_debit = match find(tx_dtls, "CdtDbtInd") {
+        Some(element) => credit_debit(element, &location.join("CdtDbtInd"))?,
+        None => entry.credit_debit,
+    };
+
+    let references = find(tx_dtls, "Refs")
+        .map(|refs| References {
+            message_id: text(refs, "MsgId"),
+            account_servicer_reference: text(refs, "AcctSvcrRef"),
+            payment_information_id: text(refs, "PmtInfId"),
+            instruction_id: text(refs, "InstrId"),
+            end_to_end_id: text(refs, "EndToEndId"),
+            transaction_id: text(refs, "TxId"),
+            mandate_id: text(refs, "MndtId"),
+            cheque_number: text(refs, "ChqNb"),
+            clearing_system_reference: text(refs, "ClrSysRef"),
+        })
+        .unwrap_or_default();
+
+    // corresponding party
+    let related_parties = find(tx_dtls, "RltdPties").map(|rltd_pties| RelatedParties {
+        debtor: find(rltd_pties, "Dbtr").map(party_parser),
+        debtor_account: find(rltd_pties, "DbtrAcct").map(account_parser),
+        creditor: find(rltd_pties, "Cdtr").map(party_parser),
+        creditor_account: find(rltd_pties, "CdtrAcct").map(account_parser),
+        ultimate_debtor: find(rltd_pties, "UltmtDbtr").map(party_parser),
+        ultimate_creditor: find(rltd_pties, "UltmtCdtr").map(party_parser),
+    });
+    let related_agents = find(tx_dtls, "RltdAgts").map(|rltd_agts| RelatedAgents {
+        debtor_agent: find(rltd_agts, "DbtrAgt").map(agent_parser),
+        creditor_agent: find(rltd_agts, "CdtrAgt").map(agent_parser),
+    });
+
+    // Remote Information / Ustrd and Strd
+    let remittance_information = match find(tx_dtls, "RmtInf") {
+        Some(rmt_inf) => Some(rmtinf_parser(rmt_inf, &location.join("RmtInf"))?),
+        None => None,
+    };
+
+    Ok(TransactionDetails {
+        references,
+        amount,
+        credit_debit,
+        amount_details: find(tx_dtls, "AmtDtls")
+            .map(|amt_dtls| amount_details_parser(amt_dtls, &location.join("AmtDtls")))
+            .transpose()?,
+        bank_transaction_code: find(tx_dtls, "BkTxCd").map(bktxcd_parser),
+        charges: charges_parser(tx_dtls, location)?,
+        interest: interest_parser(tx_dtls, location)?,
+        related_parties,
+        related_agents,
+        remittance_information,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CamtError;
+    use crate::fixture::{
+        balance, camt052, camt053, details, entry, message, parse, statement, transaction,
+    };
+    use crate::model::{
+        AvailabilityDate, DateAndDateTime, Entry, EntryStatus, MessageKind, PartyIdentification,
+    };
+    use crate::money::Currency;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    /// The first entry of a message of `version` made of `ntry`.
+    fn first_entry(version: &str, ntry: &str) -> Entry {
+        let xml = message(version, "", &[statement("S1", 1, 1, 31, ntry)]);
+        parse(&xml).statements[0].entries.remove(0)
+    }
+
+    fn parse_error(version: &str, statements: &[String]) -> CamtError {
+        crate::parse_str(&message(version, "", statements)).unwrap_err()
+    }
+
+    fn entry_status(version: &str, sts: &str) -> Option<EntryStatus> {
+        let xml = message(
+            version,
+            "",
+            &[statement(
+                "S1",
+                1,
+                1,
+                31,
+                &entry("50.00", "CRDT", sts, "A1", ""),
+            )],
+        );
+        parse(&xml).statements[0].entries[0].status.clone()
+    }
+
+    #[test]
+    fn status_as_declared() {
+        assert_eq!(
+            entry_status("camt.053.001.04", "BOOK"),
+            Some(EntryStatus::Booked)
+        );
+        assert_eq!(
+            entry_status("camt.053.001.08", "<Cd>PDNG</Cd>"),
+            Some(EntryStatus::Pending)
+        );
+    }
+
+    #[test]
+    fn status_in_the_other_shape() {
+        assert_eq!(
+            entry_status("camt.053.001.04", "<Cd>BOOK</Cd>"),
+            Some(EntryStatus::Booked)
+        );
+        assert_eq!(
+            entry_status("camt.053.001.04", "<Prtry>XYZ</Prtry>"),
+            Some(EntryStatus::Other("XYZ".to_string()))
+        );
+        assert_eq!(
+            entry_status("camt.053.001.08", "INFO"),
+            Some(EntryStatus::Information)
+        );
+    }
+
+    #[test]
+    fn account_without_identifier() {
+        let xml = message(
+            "camt.053.001.08",
+            "",
+            &[statement("S1", 1, 1, 31, "").replace(
+                "<Id><IBAN>CH9300762011623852957</IBAN></Id>",
+                "<Nm>ACME SA</Nm>",
+            )],
+        );
+        let error = crate::parse_str(&xml).unwrap_err();
+        assert!(matches!(error, CamtError::MissingElement { .. }));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Acct/Id"
+        );
+    }
+
+    #[test]
+    fn error_locations() {
+        let error = parse_error(
+            "camt.053.001.04",
+            &[statement(
+                "S1",
+                1,
+                1,
+                31,
+                &[
+                    entry("10.00", "CRDT", "BOOK", "A1", ""),
+                    entry("1O.00", "CRDT", "BOOK", "A2", ""),
+                ]
+                .concat(),
+            )],
+        );
+        assert!(matches!(error, CamtError::BadAmount { ref value, .. } if value == "1O.00"));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[2]/Amt"
+        );
+        assert_eq!(error.location().entry, Some(2));
+        assert_eq!(
+            error.location().to_string(),
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[2]/Amt (entry 2)"
+        );
+
+        let ntry = entry("10.00", "CRDT", "BOOK", "A1", "")
+            .replace("<BookgDt><Dt>2023-05-02</Dt></BookgDt>", "");
+        let error = parse_error("camt.053.001.04", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert!(matches!(error, CamtError::MissingElement { .. }));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/BookgDt"
+        );
+
+        let ntry = entry(
+            "30.00",
+            "CRDT",
+            "BOOK",
+            "A1",
+            &details(&[
+                transaction("10.00", "CRDT", ""),
+                transaction("20.00", "CRDX", ""),
+            ]),
+        );
+        let error = parse_error("camt.053.001.04", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert!(matches!(error, CamtError::BadCode { .. }));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/NtryDtls/TxDtls[2]/CdtDbtInd"
+        );
+
+        let error = crate::parse_str(
+            "<Document xmlns=\"urn:iso:std:iso:20022:tech:xsd:pain.001.001.09\">\
+             <CstmrCdtTrfInitn/></Document>",
+        )
+        .unwrap_err();
+        assert!(
+            matches!(error, CamtError::UnsupportedMessage { ref message, .. } if message == "CstmrCdtTrfInitn")
+        );
+
+        let xml = message("camt.053.001.04", "", &[statement("S1", 1, 1, 31, "")])
+            .replace("camt.053.001.04", "camt.054.001.04");
+        let error = crate::parse_str(&xml).unwrap_err();
+        assert!(matches!(
+            error,
+            CamtError::UnsupportedMessage { ref message, .. }
+                if message == "camt.054.001.04 namespace with camt.053 content"
+        ));
+    }
+
+    #[test]
+    fn date_or_date_time() {
+        let ntry = entry(
+            "10.00",
+            "CRDT",
+            "BOOK",
+            "A1",
+            "<ValDt><Dt>2023-05-03</Dt></ValDt>",
+        )
+        .replace(
+            "<BookgDt><Dt>2023-05-02</Dt></BookgDt>",
+            "<BookgDt><DtTm>2023-05-02T23:30:00+02:00</DtTm></BookgDt>",
+        );
+        let parsed = first_entry("camt.053.001.08", &ntry);
+        let may = |day| NaiveDate::from_ymd_opt(2023, 5, day).unwrap();
+        // the time as written, the offset is dropped
+        assert_eq!(
+            parsed.booking_date,
+            DateAndDateTime::DateTime(may(2).and_hms_opt(23, 30, 0).unwrap())
+        );
+        assert_eq!(parsed.booking_date.date(), may(2));
+        assert_eq!(parsed.value_date, Some(DateAndDateTime::Date(may(3))));
+        assert_eq!(
+            parsed.value_date.unwrap().date_time(),
+            may(3).and_hms_opt(0, 0, 0).unwrap()
+        );
+
+        let ntry = entry(
+            "10.00",
+            "CRDT",
+            "BOOK",
+            "A1",
+            "<ValDt><Dt>03.05.2023</Dt></ValDt>",
+        );
+        let error = parse_error("camt.053.001.04", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert!(matches!(error, CamtError::BadDate { ref value, .. } if value == "03.05.2023"));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/ValDt/Dt"
+        );
+    }
+
+    #[test]
+    fn availability() {
+        let parsed = first_entry(
+            "camt.053.001.04",
+            &entry(
+                "100.00",
+                "CRDT",
+                "BOOK",
+                "A1",
+                "<Avlbty><Dt><NbOfDays>+1</NbOfDays></Dt><Amt Ccy=\"CHF\">60.00</Amt>\
+                 <CdtDbtInd>CRDT</CdtDbtInd></Avlbty>\
+                 <Avlbty><Dt><ActlDt>2023-05-05</ActlDt></Dt><Amt Ccy=\"CHF\">40.00</Amt>\
+                 <CdtDbtInd>CRDT</CdtDbtInd></Avlbty>",
+            ),
+        );
+        assert_eq!(parsed.availability.len(), 2);
+        assert_eq!(parsed.availability[0].date, AvailabilityDate::Days(1));
+        assert_eq!(parsed.availability[0].amount.amount, Decimal::new(6000, 2));
+        assert_eq!(
+            parsed.availability[1].date,
+            AvailabilityDate::Date(NaiveDate::from_ymd_opt(2023, 5, 5).unwrap())
+        );
+
+        let ntry = entry(
+            "100.00",
+            "CRDT",
+            "BOOK",
+            "A1",
+            "<Avlbty><Amt Ccy=\"CHF\">60.00</Amt><CdtDbtInd>CRDT</CdtDbtInd></Avlbty>",
+        );
+        let error = parse_error("camt.053.001.04", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/Avlbty[1]/Dt/ActlDt"
+        );
+    }
+
+    #[test]
+    fn several_statements() {
+        let statements = camt053(&[
+            statement("S1", 1, 1, 31, &entry("10.00", "CRDT", "BOOK", "A1", "")),
+            statement(
+                "S2",
+                1,
+                1,
+                31,
+                &[
+                    entry("20.00", "DBIT", "BOOK", "B1", ""),
+                    entry("30.00", "CRDT", "BOOK", "B2", ""),
+                ]
+                .concat(),
+            )
+            .replace("CH9300762011623852957", "CH5604835012345678009"),
+        ]);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].id, "S1");
+        assert_eq!(statements[0].entries.len(), 1);
+        assert_eq!(statements[1].id, "S2");
+        assert_eq!(statements[1].entries.len(), 2);
+        assert_eq!(
+            statements[1].account.identifier().as_deref(),
+            Some("CH5604835012345678009")
+        );
+
+        let error = parse_error(
+            "camt.053.001.04",
+            &[
+                statement("S1", 1, 1, 31, ""),
+                statement("S2", 2, 1, 31, &balance("CLBD", "")),
+            ],
+        );
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[2]/Bal[1]/Amt"
+        );
+
+        let error = parse_error("camt.053.001.04", &[]);
+        assert_eq!(error.location().path, "Document/BkToCstmrStmt/Stmt");
+    }
+
+    #[test]
+    fn pending_entries_of_a_report() {
+        let reports = camt052(&[statement(
+            "R1",
+            1,
+            2,
+            2,
+            &[
+                balance("ITBD", "500.00"),
+                entry("10.00", "CRDT", "BOOK", "A1", ""),
+                entry("20.00", "DBIT", "PDNG", "A2", ""),
+            ]
+            .concat(),
+        )]);
+        assert_eq!(reports[0].kind, MessageKind::Report);
+        assert_eq!(reports[0].id, "R1");
+        assert!(!reports[0].entries[0].is_pending());
+        assert!(reports[0].entries[1].is_pending());
+
+        let document = parse(&message(
+            "camt.052.001.08",
+            "",
+            &[statement("R1", 1, 2, 2, "")],
+        ));
+        assert_eq!(document.kind, MessageKind::Report);
+        assert_eq!(document.version.unwrap().to_string(), "camt.052.001.08");
+    }
+
+    #[test]
+    fn version_from_namespace() {
+        let document = parse(&message(
+            "camt.054.001.04.ch.02",
+            "",
+            &[statement("N1", 1, 2, 2, "")],
+        ));
+        assert_eq!(document.kind, MessageKind::Notification);
+        assert_eq!(
+            document.namespace,
+            "urn:iso:std:iso:20022:tech:xsd:camt.054.001.04.ch.02"
+        );
+        let version = document.version.unwrap();
+        assert_eq!((version.version, version.swiss_version), (4, Some(2)));
+
+        // an unknown namespace is parsed as well as possible
+        let xml = message("camt.053.001.04", "", &[statement("S1", 1, 1, 31, "")]).replace(
+            "urn:iso:std:iso:20022:tech:xsd:camt.053.001.04",
+            "urn:bank:statement",
+        );
+        let document = parse(&xml);
+        assert_eq!(document.kind, MessageKind::Statement);
+        assert_eq!(document.version, None);
+    }
+
+    #[test]
+    fn version_2_transaction_amounts() {
+        // the amount is in AmtDtls only
+        let parsed = first_entry(
+            "camt.053.001.02",
+            &entry(
+                "30.00",
+                "CRDT",
+                "BOOK",
+                "A1",
+                &details(&[
+                    "<TxDtls><AmtDtls><TxAmt><Amt Ccy=\"CHF\">10.00</Amt></TxAmt></AmtDtls></TxDtls>"
+                        .to_string(),
+                    "<TxDtls><AmtDtls><TxAmt><Amt Ccy=\"CHF\">20.00</Amt></TxAmt></AmtDtls></TxDtls>"
+                        .to_string(),
+                ]),
+            ),
+        );
+        let transactions = &parsed.details[0].transactions;
+        assert_eq!(transactions[0].amount.amount, Decimal::new(1000, 2));
+        assert_eq!(transactions[1].amount.amount, Decimal::new(2000, 2));
+        assert_eq!(transactions[1].credit_debit, parsed.credit_debit);
+
+        // a single transaction without amount is the entry itself
+        let parsed = first_entry(
+            "camt.053.001.02",
+            &entry(
+                "30.00",
+                "DBIT",
+                "BOOK",
+                "A1",
+                &details(
+                    &["<TxDtls><Refs><EndToEndId>E1</EndToEndId></Refs></TxDtls>".to_string()],
+                ),
+            ),
+        );
+        assert_eq!(parsed.details[0].transactions[0].amount, parsed.amount);
+
+        // but not one of several
+        let ntry = entry(
+            "30.00",
+            "DBIT",
+            "BOOK",
+            "A1",
+            &details(&["<TxDtls/>".to_string(), transaction("30.00", "DBIT", "")]),
+        );
+        let error = parse_error("camt.053.001.02", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/NtryDtls/TxDtls[1]/Amt"
+        );
+    }
+
+    #[test]
+    fn currency_exchange() {
+        let amount_details = "<AmtDtls><InstdAmt><Amt Ccy=\"USD\">55.00</Amt>\
+                              <CcyXchg><SrcCcy>USD</SrcCcy><TrgtCcy>CHF</TrgtCcy>\
+                              <UnitCcy>USD</UnitCcy><XchgRate>0.9</XchgRate>\
+                              <QtnDt>2023-05-01T10:00:00</QtnDt></CcyXchg></InstdAmt>\
+                              <TxAmt><Amt Ccy=\"CHF\">49.50</Amt></TxAmt></AmtDtls>";
+        let parsed = first_entry(
+            "camt.053.001.04",
+            &entry(
+                "49.50",
+                "DBIT",
+                "BOOK",
+                "A1",
+                &details(&[transaction("49.50", "DBIT", amount_details)]),
+            ),
+        );
+        let amount_details = parsed.details[0].transactions[0]
+            .amount_details
+            .as_ref()
+            .unwrap();
+        let chf = Currency::new("CHF").unwrap();
+        let foreign = amount_details.foreign_amount(&chf).unwrap();
+        assert_eq!(foreign.amount.amount, Decimal::new(5500, 2));
+        assert_eq!(foreign.amount.currency.code(), "USD");
+        let exchange = amount_details.exchange().unwrap();
+        assert_eq!(exchange.source_currency.code(), "USD");
+        assert_eq!(exchange.target_currency, Some(chf));
+        assert_eq!(exchange.rate, Decimal::new(9, 1));
+        assert!(exchange.quotation_date.is_some());
+        assert!(amount_details.counter_value.is_none());
+
+        let ntry = entry(
+            "49.50",
+            "DBIT",
+            "BOOK",
+            "A1",
+            "<AmtDtls><InstdAmt><Amt Ccy=\"USD\">55.00</Amt>\
+             <CcyXchg><SrcCcy>US</SrcCcy><XchgRate>0.9</XchgRate></CcyXchg></InstdAmt></AmtDtls>",
+        );
+        let error = parse_error("camt.053.001.04", &[statement("S1", 1, 1, 31, &ntry)]);
+        assert!(matches!(error, CamtError::BadCode { .. }));
+        assert_eq!(
+            error.location().path,
+            "Document/BkToCstmrStmt/Stmt[1]/Ntry[1]/AmtDtls/InstdAmt/CcyXchg/SrcCcy"
+        );
+    }
+
+    #[test]
+    fn related_parties_and_agents() {
+        let parties = "<RltdPties>\
+             <Dbtr><Pty><Nm>ACME SA</Nm><PstlAdr><StrtNm>Rue du Lac</StrtNm><BldgNb>1</BldgNb>\
+             <PstCd>1000</PstCd><TwnNm>Lausanne</TwnNm><Ctry>CH</Ctry></PstlAdr>\
+             <Id><OrgId><AnyBIC>ACMECHZZ</AnyBIC></OrgId></Id></Pty></Dbtr>\
+             <DbtrAcct><Id><Othr><Id>12-3456-7</Id><SchmeNm><Prtry>CH-PC</Prtry></SchmeNm>\
+             </Othr></Id></DbtrAcct>\
+             <Cdtr><Nm>John Doe</Nm><PstlAdr><AdrLine>Bahnhofstrasse 1</AdrLine>\
+             <AdrLine>8001 Zürich</AdrLine></PstlAdr>\
+             <Id><PrvtId><Othr><Id>756.1234.5678.97</Id><SchmeNm><Cd>NIDN</Cd></SchmeNm>\
+             </Othr></PrvtId></Id></Cdtr>\
+             <CdtrAcct><Prxy><Tp><Cd>TELE</Cd></Tp><Id>+41791234567</Id></Prxy></CdtrAcct>\
+             </RltdPties>\
+             <RltdAgts><DbtrAgt><FinInstnId><BICFI>UBSWCHZH80A</BICFI></FinInstnId></DbtrAgt>\
+             <CdtrAgt><FinInstnId><BIC>POFICHBEXXX</BIC></FinInstnId></CdtrAgt></RltdAgts>";
+        let parsed = first_entry(
+            "camt.053.001.08",
+            &entry(
+                "50.00",
+                "DBIT",
+                "BOOK",
+                "A1",
+                &details(&[transaction("50.00", "DBIT", parties)]),
+            ),
+        );
+        let transaction = &parsed.details[0].transactions[0];
+        let parties = transaction.related_parties.as_ref().unwrap();
+
+        let debtor = parties.debtor.as_ref().unwrap();
+        assert_eq!(debtor.name.as_deref(), Some("ACME SA"));
+        assert_eq!(
+            debtor.postal_address.as_ref().unwrap().one_line(),
+            "Rue du Lac 1, 1000 Lausanne, CH"
+        );
+        assert_eq!(
+            debtor.identification.as_ref().unwrap().summary().as_deref(),
+            Some("BIC ACMECHZZ")
+        );
+
+        let creditor = parties.creditor.as_ref().unwrap();
+        assert_eq!(
+            creditor.postal_address.as_ref().unwrap().one_line(),
+            "Bahnhofstrasse 1, 8001 Zürich"
+        );
+        assert!(matches!(
+            creditor.identification,
+            Some(PartyIdentification::Private { .. })
+        ));
+        assert_eq!(
+            creditor
+                .identification
+                .as_ref()
+                .unwrap()
+                .summary()
+                .as_deref(),
+            Some("756.1234.5678.97 (NIDN)")
+        );
+
+        let debtor_account = parties.debtor_account.as_ref().unwrap();
+        assert_eq!(debtor_account.identifier().as_deref(), Some("12-3456-7"));
+        assert_eq!(
+            debtor_account.other.as_ref().unwrap().scheme.as_deref(),
+            Some("CH-PC")
+        );
+        let creditor_account = parties.creditor_account.as_ref().unwrap();
+        assert_eq!(
+            creditor_account.identifier().as_deref(),
+            Some("+41791234567")
+        );
+        assert_eq!(
+            creditor_account
+                .proxy
+                .as_ref()
+                .unwrap()
+                .proxy_type
+                .as_deref(),
+            Some("TELE")
+        );
+
+        let agents = transaction.related_agents.as_ref().unwrap();
+        let bic = |agent: &Option<crate::model::FinancialInstitution>| {
+            agent.as_ref().unwrap().bic.clone()
+        };
+        assert_eq!(bic(&agents.debtor_agent).as_deref(), Some("UBSWCHZH80A"));
+        assert_eq!(bic(&agents.creditor_agent).as_deref(), Some("POFICHBEXXX"));
+    }
+
+    #[test]
+    fn statement_account() {
+        let account = |acct: &str| {
+            let xml = message(
+                "camt.053.001.08",
+                "",
+                &[statement("S1", 1, 1, 31, "").replace(
+                    "<Acct><Id><IBAN>CH9300762011623852957</IBAN></Id></Acct>",
+                    acct,
+                )],
+            );
+            parse(&xml).statements.remove(0).account
+        };
+        let iban =
+            account("<Acct><Id><IBAN>ch93 0076 2011 6238 5295 7</IBAN></Id><Ccy>CHF</Ccy></Acct>");
+        assert_eq!(iban.identifier().as_deref(), Some("CH9300762011623852957"));
+        assert_eq!(iban.currency, Currency::new("CHF"));
+
+        let other = account(
+            "<Acct><Id><Othr><Id>0123456789</Id><SchmeNm><Cd>BBAN</Cd></SchmeNm>\
+             <Issr>BANK</Issr></Othr></Id></Acct>",
+        );
+        assert_eq!(other.identifier().as_deref(), Some("0123456789"));
+        let othr = other.other.unwrap();
+        assert_eq!(othr.scheme.as_deref(), Some("BBAN"));
+        assert_eq!(othr.issuer.as_deref(), Some("BANK"));
+
+        let proxy = account(
+            "<Acct><Prxy><Tp><Prtry>EMAIL</Prtry></Tp><Id>acme@example.ch</Id></Prxy></Acct>",
+        );
+        assert_eq!(proxy.identifier().as_deref(), Some("acme@example.ch"));
+        assert_eq!(proxy.proxy.unwrap().proxy_type.as_deref(), Some("EMAIL"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Check digits of payment references and account identifiers.
+
+/// Digits of `reference` once spaces are removed, `None` if anything else
+/// is left.
+fn digits(reference: &str) -> Option<Vec<u32>> {
+    reference
+        .chars()
+        .filter(|c| *c != ' ')
+        .map(|c| c.to_digit(10))
+        .collect()
+}
+
+/// Recursive modulo 10 of the last digit, used by QR and ISR references.
+fn has_mod10_check_digit(digits: &[u32]) -> bool {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+    let (check, payload) = match digits.split_last() {
+        Some(split) => split,
+        None => return false,
+    };
+    let carry = payload
+        .iter()
+        .fold(0, |carry, digit| TABLE[((carry + digit) % 10) as usize]);
+    (10 - carry) % 10 == *check
+}
+
+/// Swiss QR-reference, 27 digits checked with the recursive modulo 10 of
+/// the last one.
+pub fn is_valid_qr_reference(reference: &str) -> bool {
+    match digits(reference) {
+        Some(digits) => digits.len() == 27 && has_mod10_check_digit(&digits),
+        None => false,
+    }
+}
+
+/// ISR reference of the former orange payment slip, up to 27 digits,
+/// checked like a QR-reference.
+pub fn is_valid_isr_reference(reference: &str) -> bool {
+    match digits(reference) {
+        Some(digits) => (2..=27).contains(&digits.len()) && has_mod10_check_digit(&digits),
+        None => false,
+    }
+}
+
+/// ISO 11649 creditor reference, `RF`, two check digits and up to 21
+/// letters or digits, checked with ISO 7064 modulo 97.
+pub fn is_valid_creditor_reference(reference: &str) -> bool {
+    let reference: String = reference
+        .chars()
+        .filter(|c| *c != ' ')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if !reference.starts_with("RF")
+        || !(5..=25).contains(&reference.len())
+        || !reference.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return false;
+    }
+    let (head, tail) = reference.split_at(4);
+    mod97(&format!("{}{}", tail, head)) == Some(1)
+}
+
+/// Remainder modulo 97 of an alphanumeric string, letters counting as
+/// 10 (`A`) to 35 (`Z`), as used by IBAN and RF references.
+pub(crate) fn mod97(value: &str) -> Option<u32> {
+    let mut remainder = 0;
+    for c in value.chars() {
+        let number = c.to_digit(36)?;
+        remainder = if number < 10 {
+            (remainder * 10 + number) % 97
+        } else {
+            (remainder * 100 + number) % 97
+        };
+    }
+    Some(remainder)
+}
+
+/// Length of the IBANs of a country, from the SWIFT IBAN registry.
+fn iban_length(country: &str) -> Option<usize> {
+    Some(match country {
+        "NO" => 15,
+        "BE" => 16,
+        "DK" | "FI" | "FK" | "FO" | "GL" | "NL" | "SD" => 18,
+        "MK" | "SI" => 19,
+        "AT" | "BA" | "EE" | "KZ" | "LT" | "LU" | "MN" | "XK" => 20,
+        "CH" | "HR" | "LI" | "LV" => 21,
+        "BG" | "BH" | "CR" | "DE" | "GB" | "GE" | "IE" | "ME" | "RS" | "VA" => 22,
+        "AE" | "GI" | "IL" | "IQ" | "OM" | "SO" | "TL" => 23,
+        "AD" | "CZ" | "ES" | "MD" | "PK" | "RO" | "SA" | "SE" | "SK" | "TN" | "VG" => 24,
+        "LY" | "PT" | "ST" => 25,
+        "IS" | "TR" => 26,
+        "BI" | "DJ" | "FR" | "GR" | "IT" | "MC" | "MR" | "SM" => 27,
+        "AL" | "AZ" | "BY" | "CY" | "DO" | "GT" | "HU" | "LB" | "NI" | "PL" | "SV" => 28,
+        "BR" | "EG" | "PS" | "QA" | "UA" => 29,
+        "JO" | "KW" | "MU" | "YE" => 30,
+        "MT" | "SC" => 31,
+        "LC" => 32,
+        "RU" => 33,
+        _ => return None,
+    })
+}
+
+/// What is wrong with an IBAN in electronic format, `None` if it is
+/// valid. Countries missing from the registry are only checked for the
+/// ISO 13616 maximum length.
+pub(crate) fn iban_problem(iban: &str) -> Option<&'static str> {
+    if iban.len() < 5
+        || !iban.is_ascii()
+        || !iban[..2].chars().all(|c| c.is_ascii_uppercase())
+        || !iban[2..4].chars().all(|c| c.is_ascii_digit())
+        || !iban[4..]
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return Some("not an IBAN");
+    }
+    let length_ok = match iban_length(&iban[..2]) {
+        Some(length) => iban.len() == length,
+        None => iban.len() <= 34,
+    };
+    if !length_ok {
+        return Some("wrong length for its country");
+    }
+    let (head, tail) = iban.split_at(4);
+    if mod97(&format!("{}{}", tail, head)) != Some(1) {
+        return Some("wrong check digits");
+    }
+    None
+}
+
+/// ISO 13616 IBAN in electronic format, e.g. `CH9300762011623852957`:
+/// country, check digits, country specific length and modulo 97.
+pub fn is_valid_iban(iban: &str) -> bool {
+    iban_problem(iban).is_none()
+}
+
+/// ISO 9362 BIC: 4 letters or digits for the institution, 2 letters for
+/// the country, 2 letters or digits for the location, and optionally 3
+/// for the branch.
+pub fn is_valid_bic(bic: &str) -> bool {
+    let alphanumeric = |part: &str| {
+        part.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    };
+    (bic.len() == 8 || bic.len() == 11)
+        && bic.is_ascii()
+        && alphanumeric(&bic[..4])
+        && bic[4..6].chars().all(|c| c.is_ascii_uppercase())
+        && alphanumeric(&bic[6..])
+}
+
+/// An IBAN in groups of four, as printed: `CH93 0076 2011 6238 5295 7`.
+pub fn format_iban(iban: &str) -> String {
+    iban.chars()
+        .collect::<Vec<_>>()
+        .chunks(4)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qr_reference() {
+        assert!(is_valid_qr_reference("210000000003139471430009017"));
+        assert!(is_valid_qr_reference("21 00000 00003 13947 14300 09017"));
+        assert!(!is_valid_qr_reference("210000000003139471430009018"));
+        assert!(!is_valid_qr_reference("21000000000313947143000901A"));
+    }
+
+    #[test]
+    fn qr_reference_has_27_digits() {
+        // 11 has a correct check digit, but is no QR-reference
+        assert!(!is_valid_qr_reference("11"));
+        assert!(is_valid_isr_reference("11"));
+        assert!(!is_valid_isr_reference("12"));
+        assert!(!is_valid_isr_reference("1"));
+    }
+
+    #[test]
+    fn creditor_reference() {
+        assert!(is_valid_creditor_reference("RF18539007547034"));
+        assert!(is_valid_creditor_reference("rf18 5390 0754 7034"));
+        assert!(!is_valid_creditor_reference("RF18539007547035"));
+        assert!(!is_valid_creditor_reference("RX18539007547034"));
+        assert!(!is_valid_creditor_reference("RF18"));
+    }
+
+    #[test]
+    fn iban() {
+        assert!(is_valid_iban("CH9300762011623852957"));
+        assert!(is_valid_iban("DE89370400440532013000"));
+        assert_eq!(
+            iban_problem("CH9300762011623852958"),
+            Some("wrong check digits")
+        );
+        assert_eq!(
+            iban_problem("CH930076201162385295"),
+            Some("wrong length for its country")
+        );
+        assert_eq!(
+            iban_problem("CH93 0076 2011 6238 5295 7"),
+            Some("not an IBAN")
+        );
+        assert_eq!(iban_problem("9300762011623852957"), Some("not an IBAN"));
+    }
+
+    #[test]
+    fn bic() {
+        assert!(is_valid_bic("UBSWCHZH80A"));
+        assert!(is_valid_bic("UBSWCHZH"));
+        assert!(!is_valid_bic("UBSW1HZH80A"));
+        assert!(!is_valid_bic("UBSWCHZH80"));
+        assert!(!is_valid_bic("ubswchzh80a"));
+    }
+
+    #[test]
+    fn print_format() {
+        assert_eq!(
+            format_iban("CH9300762011623852957"),
+            "CH93 0076 2011 6238 5295 7"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Checks across statements of one account: missing, duplicated and
+//! overlapping statements, and breaks in the balance chain.
+
+use crate::model::{BalanceType, MessageKind, Statement};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Something wrong in the succession of the statements of an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceIssue {
+    /// the same statement was given twice
+    Duplicate { account: String, id: String },
+    /// sequence numbers between `after` and `before` are missing
+    Gap {
+        account: String,
+        after: u64,
+        before: u64,
+    },
+    /// the periods of two statements overlap
+    Overlap {
+        account: String,
+        first: String,
+        second: String,
+    },
+    /// the closing balance of a statement is not the opening of the next
+    BalanceBreak {
+        account: String,
+        previous: String,
+        next: String,
+        closing: Decimal,
+        opening: Decimal,
+    },
+}
+
+impl fmt::Display for SequenceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceIssue::Duplicate { account, id } => {
+                write!(f, "{}: statement {} given more than once", account, id)
+            }
+            SequenceIssue::Gap {
+                account,
+                after,
+                before,
+            } if before - after == 2 => {
+                write!(f, "{}: sequence number {} is missing", account, after + 1)
+            }
+            SequenceIssue::Gap {
+                account,
+                after,
+                before,
+            } => write!(
+                f,
+                "{}: sequence numbers {} to {} are missing",
+                account,
+                after + 1,
+                before - 1
+            ),
+            SequenceIssue::Overlap {
+                account,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: statements {} and {} overlap",
+                account, first, second
+            ),
+            SequenceIssue::BalanceBreak {
+                account,
+                previous,
+                next,
+                closing,
+                opening,
+            } => write!(
+                f,
+                "{}: statement {} closes at {} but {} opens at {}",
+                account, previous, closing, next, opening
+            ),
+        }
+    }
+}
+
+/// `ElctrncSeqNb`, or `LglSeqNb` when the bank only sends that one.
+fn sequence_number(statement: &Statement) -> Option<u64> {
+    statement
+        .electronic_sequence_number
+        .or(statement.legal_sequence_number)
+}
+
+/// Check the camt.053 statements of each account follow each other without
+/// gaps, duplicates or overlaps, and that each opens where the previous one
+/// closed. Statements are ordered by period, then sequence number.
+pub fn check_sequence(statements: &[Statement]) -> Vec<SequenceIssue> {
+    let mut issues = Vec::new();
+
+    let mut accounts: Vec<String> = Vec::new();
+    for statement in statements {
+        let account = statement.account.identifier().unwrap_or_default();
+        if statement.kind == MessageKind::Statement && !accounts.contains(&account) {
+            accounts.push(account);
+        }
+    }
+
+    for account in accounts {
+        let mut sorted: Vec<&Statement> = statements
+            .iter()
+            .filter(|statement| {
+                statement.kind == MessageKind::Statement
+                    && statement.account.identifier().unwrap_or_default() == account
+            })
+            .collect();
+        sorted.sort_by_key(|statement| {
+            (
+                statement.from_to.as_ref().map(|period| period.from),
+                statement.creation_date_time,
+                sequence_number(statement),
+            )
+        });
+
+        // duplicates are reported once and left out of the other checks
+        let mut distinct: Vec<&Statement> = Vec::new();
+        for statement in sorted {
+            let is_duplicate = distinct.iter().any(|known| {
+                known.id == statement.id
+                    || (sequence_number(known).is_some()
+                        && sequence_number(known) == sequence_number(statement)
+                        && known.from_to == statement.from_to)
+            });
+            if is_duplicate {
+                issues.push(SequenceIssue::Duplicate {
+                    account: account.to_string(),
+                    id: statement.id.clone(),
+                });
+            } else {
+                distinct.push(statement);
+            }
+        }
+
+        for pair in distinct.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+
+            // a lower number is taken as a restart, e.g. at new year
+            if let (Some(after), Some(before)) = (sequence_number(previous), sequence_number(next))
+            {
+                if before > after + 1 {
+                    issues.push(SequenceIssue::Gap {
+                        account: account.to_string(),
+                        after,
+                        before,
+                    });
+                }
+            }
+
+            if let (Some(first), Some(second)) = (&previous.from_to, &next.from_to) {
+                if first.to > second.from {
+                    issues.push(SequenceIssue::Overlap {
+                        account: account.to_string(),
+                        first: previous.id.clone(),
+                        second: next.id.clone(),
+                    });
+                }
+            }
+
+            let closing = previous.balance(&BalanceType::Closing);
+            let opening = next
+                .balance(&BalanceType::Opening)
+                .or_else(|| next.balance(&BalanceType::PreviouslyClosed));
+            if let (Some(closing), Some(opening)) = (closing, opening) {
+                if closing.signed_amount() != opening.signed_amount() {
+                    issues.push(SequenceIssue::BalanceBreak {
+                        account: account.to_string(),
+                        previous: previous.id.clone(),
+                        next: next.id.clone(),
+                        closing: closing.signed_amount(),
+                        opening: opening.signed_amount(),
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{balance, camt053, statement};
+
+    fn balances(opening: &str, closing: &str) -> String {
+        [balance("OPBD", opening), balance("CLBD", closing)].concat()
+    }
+
+    #[test]
+    fn consecutive_statements() {
+        let statements = camt053(&[
+            statement("S1", 1, 1, 10, &balances("100.00", "200.00")),
+            statement("S2", 2, 11, 20, &balances("200.00", "300.00")),
+        ]);
+        assert_eq!(check_sequence(&statements), Vec::new());
+    }
+
+    #[test]
+    fn gap() {
+        let statements = camt053(&[
+            statement("S1", 1, 1, 10, &balances("100.00", "200.00")),
+            statement("S4", 4, 11, 20, &balances("200.00", "300.00")),
+        ]);
+        assert_eq!(
+            check_sequence(&statements),
+            vec![SequenceIssue::Gap {
+                account: "CH9300762011623852957".to_string(),
+                after: 1,
+                before: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn overlap_and_balance_break() {
+        let statements = camt053(&[
+            statement("S2", 2, 8, 20, &balances("150.00", "300.00")),
+            statement("S1", 1, 1, 10, &balances("100.00", "200.00")),
+        ]);
+        assert_eq!(
+            check_sequence(&statements),
+            vec![
+                SequenceIssue::Overlap {
+                    account: "CH9300762011623852957".to_string(),
+                    first: "S1".to_string(),
+                    second: "S2".to_string(),
+                },
+                SequenceIssue::BalanceBreak {
+                    account: "CH9300762011623852957".to_string(),
+                    previous: "S1".to_string(),
+                    next: "S2".to_string(),
+                    closing: Decimal::new(20000, 2),
+                    opening: Decimal::new(15000, 2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate() {
+        let statement = statement("S1", 1, 1, 10, &balances("100.00", "200.00"));
+        let statements = camt053(&[statement.clone(), statement]);
+        assert_eq!(
+            check_sequence(&statements),
+            vec![SequenceIssue::Duplicate {
+                account: "CH9300762011623852957".to_string(),
+                id: "S1".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Readable labels for the ISO 20022 bank transaction codes, the part of
+//! the external code list that shows up on customer accounts.
+
+/// `Domn/Cd`
+fn domain(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "ACMT" => "account management",
+        "CAMT" => "cash management",
+        "CMDT" => "commodities",
+        "DERV" => "derivatives",
+        "FORX" => "foreign exchange",
+        "LDAS" => "loans, deposits and syndications",
+        "PMET" => "precious metal",
+        "PMNT" => "payments",
+        "SECU" => "securities",
+        "TRAD" => "trade services",
+        "XTND" => "extended domain",
+        _ => return None,
+    })
+}
+
+/// `Domn/Fmly/Cd`, with the direction it gives to a sub family.
+fn family(code: &str) -> Option<(&'static str, Option<&'static str>)> {
+    Some(match code {
+        "RCDT" => ("received credit transfer", Some("received")),
+        "ICDT" => ("issued credit transfer", Some("issued")),
+        "RRCT" => ("received real-time credit transfer", Some("received")),
+        "IRCT" => ("issued real-time credit transfer", Some("issued")),
+        "RDDT" => ("received direct debit", Some("received")),
+        "IDDT" => ("issued direct debit", Some("issued")),
+        "RCHQ" => ("received cheque", Some("received")),
+        "ICHQ" => ("issued cheque", Some("issued")),
+        "CCRD" => ("customer card transaction", None),
+        "MCRD" => ("merchant card transaction", None),
+        "CNTR" => ("counter transaction", None),
+        "DRFT" => ("draft", None),
+        "LBOX" => ("lockbox transaction", None),
+        "MCOP" => ("miscellaneous credit operation", None),
+        "MDOP" => ("miscellaneous debit operation", None),
+        "OPCL" => ("account opening and closing", None),
+        "ACOP" => ("additional miscellaneous credit operation", None),
+        "ADOP" => ("additional miscellaneous debit operation", None),
+        "CASH" => ("cash account", None),
+        _ => return None,
+    })
+}
+
+/// `Domn/Fmly/SubFmlyCd`, `OTHR` and `NTAV` say nothing more than the family.
+fn sub_family(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "ESCT" => "SEPA credit transfer",
+        "DMCT" => "domestic credit transfer",
+        "XBCT" => "cross-border credit transfer",
+        "SALA" => "salary payment",
+        "STDO" => "standing order",
+        "BOOK" => "internal book transfer",
+        "AUTT" => "automatic transfer",
+        "SDVA" => "same day value credit transfer",
+        "PRCT" => "priority credit transfer",
+        "VCOM" => "credit transfer with commercial information",
+        "ESDD" => "SEPA core direct debit",
+        "BBDD" => "SEPA B2B direct debit",
+        "PMDD" => "direct debit",
+        "URDD" => "direct debit under reserve",
+        "OODD" => "one-off direct debit",
+        "POSD" => "point-of-sale debit card payment",
+        "POSC" => "credit card payment",
+        "SMRT" => "smart-card payment",
+        "CWDL" => "cash withdrawal",
+        "CDPT" => "cash deposit",
+        "XBCW" => "cross-border cash withdrawal",
+        "CCHQ" => "cheque",
+        "CHKD" => "cheque deposit",
+        "UPCQ" => "unpaid cheque",
+        "CHRG" => "charges",
+        "FEES" => "fees",
+        "COMM" => "commission",
+        "INTR" => "interest",
+        "TAXE" => "taxes",
+        "ADJT" => "adjustment",
+        "CAJT" => "credit adjustment",
+        "DAJT" => "debit adjustment",
+        "RRTN" => "reversal due to payment return",
+        "RPCR" => "reversal due to payment cancellation request",
+        "UPDD" => "reversal due to return or unpaid direct debit",
+        _ => return None,
+    })
+}
+
+/// Label of a domain, family and sub family, as precise as the codes
+/// known allow, e.g. `PMNT/RCDT/ESCT` gives "SEPA credit transfer received"
+/// and `PMNT/RCDT/OTHR` "received credit transfer".
+pub(crate) fn label(
+    domain_code: &str,
+    family_code: Option<&str>,
+    sub_family_code: Option<&str>,
+) -> Option<String> {
+    let domain_label = domain(domain_code)?;
+    let (family_label, direction) = match family_code.and_then(family) {
+        Some(family) => family,
+        None => return Some(domain_label.to_string()),
+    };
+    let label = match (sub_family_code.and_then(sub_family), direction) {
+        (Some(sub_family_label), Some(direction)) => format!("{} {}", sub_family_label, direction),
+        (Some(sub_family_label), None) => sub_family_label.to_string(),
+        (None, _) => family_label.to_string(),
+    };
+    Some(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::label;
+    use crate::model::BankTransactionCode;
+
+    fn iso(domain: &str, family: &str, sub_family: &str) -> BankTransactionCode {
+        BankTransactionCode {
+            domain: Some(domain.to_string()),
+            family: Some(family.to_string()),
+            sub_family: Some(sub_family.to_string()),
+            ..BankTransactionCode::default()
+        }
+    }
+
+    #[test]
+    fn sub_family_with_direction() {
+        let code = iso("PMNT", "RCDT", "ESCT");
+        assert_eq!(code.code().as_deref(), Some("PMNT/RCDT/ESCT"));
+        assert_eq!(
+            code.label().as_deref(),
+            Some("SEPA credit transfer received")
+        );
+        assert_eq!(
+            label("PMNT", Some("ICDT"), Some("SALA")).as_deref(),
+            Some("salary payment issued")
+        );
+    }
+
+    #[test]
+    fn family_when_sub_family_says_nothing() {
+        assert_eq!(
+            iso("PMNT", "RCDT", "OTHR").label().as_deref(),
+            Some("received credit transfer")
+        );
+        assert_eq!(
+            label("PMNT", Some("RDDT"), None).as_deref(),
+            Some("received direct debit")
+        );
+        assert_eq!(label("PMNT", None, None).as_deref(), Some("payments"));
+        assert_eq!(
+            label("PMNT", Some("XXXX"), Some("ESCT")).as_deref(),
+            Some("payments")
+        );
+        assert_eq!(label("ZZZZ", Some("RCDT"), Some("ESCT")), None);
+    }
+
+    #[test]
+    fn family_without_direction() {
+        assert_eq!(
+            iso("PMNT", "CCRD", "POSD").label().as_deref(),
+            Some("point-of-sale debit card payment")
+        );
+    }
+
+    #[test]
+    fn proprietary_code() {
+        let code = BankTransactionCode {
+            proprietary: Some("N2A".to_string()),
+            proprietary_issuer: Some("BANK".to_string()),
+            ..BankTransactionCode::default()
+        };
+        assert_eq!(code.code().as_deref(), Some("N2A"));
+        assert_eq!(code.label(), None);
+
+        let code = BankTransactionCode {
+            domain: Some("PMNT".to_string()),
+            proprietary: Some("N2A".to_string()),
+            ..BankTransactionCode::default()
+        };
+        assert_eq!(code.code().as_deref(), Some("PMNT"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Message and schema version detection from the `Document` namespace.
+
+use crate::model::MessageKind;
+use std::fmt;
+
+/// A schema version such as `camt.053.001.04`, or the Swiss
+/// `camt.053.001.04.ch.02` variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaVersion {
+    pub kind: MessageKind,
+    /// `001` in `camt.053.001.04`
+    pub variant: u16,
+    /// `04` in `camt.053.001.04`
+    pub version: u16,
+    /// `02` in `camt.053.001.04.ch.02`
+    pub swiss_version: Option<u16>,
+}
+
+/// Parse a run of digits at the start of `s`, returning it and the rest.
+fn number(s: &str) -> Option<(u16, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+impl SchemaVersion {
+    /// Recognise namespaces like `urn:iso:std:iso:20022:tech:xsd:camt.053.001.04`
+    /// or `http://www.six-interbank-clearing.com/de/camt.053.001.04.ch.02.xsd`.
+    pub fn from_namespace(namespace: &str) -> Option<SchemaVersion> {
+        let (_, rest) = namespace.split_once("camt.")?;
+        let (message, rest) = number(rest)?;
+        let (variant, rest) = number(rest.strip_prefix('.')?)?;
+        let (version, rest) = number(rest.strip_prefix('.')?)?;
+        let swiss_version = rest
+            .strip_prefix(".ch.")
+            .and_then(number)
+            .map(|(swiss_version, _)| swiss_version);
+        let kind = match message {
+            52 => MessageKind::Report,
+            53 => MessageKind::Statement,
+            54 => MessageKind::Notification,
+            _ => return None,
+        };
+        Some(SchemaVersion {
+            kind,
+            variant,
+            version,
+            swiss_version,
+        })
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{:03}.{:02}",
+            self.kind.name(),
+            self.variant,
+            self.version
+        )?;
+        if let Some(swiss_version) = self.swiss_version {
+            write!(f, ".ch.{:02}", swiss_version)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso_namespace() {
+        let version =
+            SchemaVersion::from_namespace("urn:iso:std:iso:20022:tech:xsd:camt.053.001.08")
+                .unwrap();
+        assert_eq!(
+            version,
+            SchemaVersion {
+                kind: MessageKind::Statement,
+                variant: 1,
+                version: 8,
+                swiss_version: None,
+            }
+        );
+        assert_eq!(version.to_string(), "camt.053.001.08");
+    }
+
+    #[test]
+    fn swiss_namespace() {
+        let version = SchemaVersion::from_namespace(
+            "http://www.six-interbank-clearing.com/de/camt.054.001.04.ch.02.xsd",
+        )
+        .unwrap();
+        assert_eq!(version.kind, MessageKind::Notification);
+        assert_eq!((version.version, version.swiss_version), (4, Some(2)));
+        assert_eq!(version.to_string(), "camt.054.001.04.ch.02");
+    }
+
+    #[test]
+    fn unknown_namespaces() {
+        assert_eq!(SchemaVersion::from_namespace(""), None);
+        assert_eq!(
+            SchemaVersion::from_namespace("urn:iso:std:iso:20022:tech:xsd:pain.001.001.09"),
+            None
+        );
+        assert_eq!(
+            SchemaVersion::from_namespace("urn:iso:std:iso:20022:tech:xsd:camt.056.001.08"),
+            None
+        );
+        assert_eq!(
+            SchemaVersion::from_namespace("urn:iso:std:iso:20022:tech:xsd:camt.053"),
+            None
+        );
+    }
+}